@@ -167,6 +167,58 @@ decl_module! {
             Self::deposit_event(Event::<T>::WithdrawAssetViaXCMP(para_id, para_asset_id, dest, asset_id, amount));
         }
 
+        /// Swap exact amount of some asset to another parachain asset
+        /// and transfer swap output to the `dest` account on its parachain.
+        #[weight = 10]
+        fn swap_and_transfer_to_parachain_chain(
+            origin,
+            asset_in: Asset<AssetIdOf<T>>,
+            asset_in_amount: BalanceOf<T>,
+            para_id: u32,
+            dest: T::AccountId,
+            para_asset_id: Option<AssetIdOf<T>>,
+            min_asset_out_amount: BalanceOf<T>,
+        ) {
+            let who = ensure_signed(origin)?;
+
+            // Ensure provided balance amount is greater than zero
+            Self::ensure_non_zero_balance(asset_in_amount)?;
+
+            // Ensure expected swap output is greater than zero, so that swap output can always be transferred
+            Self::ensure_non_zero_balance(min_asset_out_amount)?;
+
+            let para_id: ParaId = para_id.into();
+
+            // Retreive our internal para asset id representation (swap output should be transferable to its parachain)
+            let asset_id = Self::ensure_asset_id_exists(para_id, para_asset_id)?;
+
+            // Perform swap (all swap related checks are performed before any mutation)
+            let amount = <pallet_subdex::Module<T>>::perform_swap(
+                &who,
+                asset_in,
+                asset_in_amount,
+                Asset::ParachainAsset(asset_id),
+                min_asset_out_amount,
+                &who,
+                None
+            )?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            // Slash balance of account inernal parachain asset representation to perform withdraw
+            <pallet_subdex::Module<T>>::slash_asset(&who, Asset::ParachainAsset(asset_id), amount);
+
+            // Send xcmp transfer message
+            T::XCMPMessageSender::send_xcmp_message(
+                para_id,
+                &XCMPMessage::TransferToken(dest.clone(), amount, para_asset_id),
+            ).expect("Should not fail; qed");
+
+            Self::deposit_event(Event::<T>::WithdrawAssetViaXCMP(para_id, para_asset_id, dest, asset_id, amount));
+        }
+
     }
 }
 
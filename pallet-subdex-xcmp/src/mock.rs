@@ -26,11 +26,12 @@ pub use frame_support::traits::Get;
 use frame_support::traits::{OnFinalize, OnInitialize};
 pub use frame_support::{assert_err, assert_ok};
 use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+use frame_system::EnsureRoot;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
+    ModuleId, Perbill, Permill,
 };
 
 pub use frame_support::dispatch::DispatchResult;
@@ -210,6 +211,39 @@ parameter_types! {
     pub const FeeRateDenominator: Balance = 1000;
     pub const MinMainNetworkAssetAmount: Balance = get_min_main_network_asset_amount();
     pub const MinParachainAssetAmount: Balance = get_min_parachain_asset_amount();
+    pub const SubDexModuleId: ModuleId = ModuleId(*b"subdex00");
+    pub const MaxPriceImpact: Permill = Permill::from_percent(50);
+    pub const TwapPeriod: u64 = 10 * MILLISECS_PER_BLOCK;
+    pub const MaxBatchOperations: u32 = 4;
+    pub const MaxScheduledOrdersPerBlock: u32 = 2;
+    pub const OrderDeposit: Balance = 1_000;
+    pub const MaxOrderLifetime: u64 = 100;
+}
+
+// Flash swap receiver, which swaps borrowed asset out amount along the path of assets, SCALE encoded in flash swap data.
+// Empty data leaves borrowed amount untouched, so that flash swap is repaid from borrower own balance.
+pub struct TestFlashSwapReceiver;
+
+impl pallet_subdex::FlashSwapReceiver<Test> for TestFlashSwapReceiver {
+    fn on_flash_swap(
+        borrower: &AccountId,
+        asset_out: Asset<AssetId>,
+        asset_out_amount: Balance,
+        _asset_in: Asset<AssetId>,
+        _repayment_amount: Balance,
+        data: &[u8],
+    ) -> DispatchResult {
+        let path = Vec::<Asset<AssetId>>::decode(&mut &data[..]).unwrap_or_default();
+
+        let (mut asset, mut asset_amount) = (asset_out, asset_out_amount);
+
+        for next_asset in path {
+            asset_amount =
+                SubDex::perform_swap(borrower, asset, asset_amount, next_asset, 0, borrower, None)?;
+            asset = next_asset;
+        }
+        Ok(())
+    }
 }
 
 impl pallet_subdex::Trait for Test {
@@ -221,6 +255,15 @@ impl pallet_subdex::Trait for Test {
     type FeeRateDenominator = FeeRateDenominator;
     type MinMainNetworkAssetAmount = MinMainNetworkAssetAmount;
     type MinParachainAssetAmount = MinParachainAssetAmount;
+    type FlashSwapReceiver = TestFlashSwapReceiver;
+    type AdminOrigin = EnsureRoot<AccountId>;
+    type ModuleId = SubDexModuleId;
+    type MaxPriceImpact = MaxPriceImpact;
+    type TwapPeriod = TwapPeriod;
+    type MaxBatchOperations = MaxBatchOperations;
+    type MaxScheduledOrdersPerBlock = MaxScheduledOrdersPerBlock;
+    type OrderDeposit = OrderDeposit;
+    type MaxOrderLifetime = MaxOrderLifetime;
 }
 
 mod subdex_xcmp {
@@ -247,16 +290,19 @@ pub struct ExtBuilder;
 
 impl ExtBuilder {
     pub fn build() -> sp_io::TestExternalities {
-        let default_subdex_genesis_config = default_pallet_subdex_genesis_config();
+        Self::build_with_subdex_genesis_config(default_pallet_subdex_genesis_config())
+    }
+
+    pub fn build_with_subdex_genesis_config(
+        subdex_genesis_config: pallet_subdex::GenesisConfig<Test>,
+    ) -> sp_io::TestExternalities {
         let default_subdex_xcmp_genesis_config = default_pallet_subdex_xcmp_genesis_config();
 
         let mut t = frame_system::GenesisConfig::default()
             .build_storage::<Test>()
             .unwrap();
 
-        default_subdex_genesis_config
-            .assimilate_storage(&mut t)
-            .unwrap();
+        subdex_genesis_config.assimilate_storage(&mut t).unwrap();
         default_subdex_xcmp_genesis_config
             .assimilate_storage(&mut t)
             .unwrap();
@@ -264,9 +310,11 @@ impl ExtBuilder {
     }
 }
 
-fn default_pallet_subdex_genesis_config() -> pallet_subdex::GenesisConfig<Test> {
+pub fn default_pallet_subdex_genesis_config() -> pallet_subdex::GenesisConfig<Test> {
     pallet_subdex::GenesisConfig {
         dex_treasury: DexTreasury::new(TreasuryAccountId::get(), 1, 4),
+        asset_balances: vec![],
+        exchanges: vec![],
     }
 }
 
@@ -343,9 +391,11 @@ pub fn assert_subdex_failure(
 pub fn run_to_block(n: u64) {
     while System::block_number() < n {
         <System as OnFinalize<u64>>::on_finalize(System::block_number());
+        <SubDex as OnFinalize<u64>>::on_finalize(System::block_number());
         <SubdexXcmp as OnFinalize<u64>>::on_finalize(System::block_number());
         System::set_block_number(System::block_number() + 1);
         <System as OnInitialize<u64>>::on_initialize(System::block_number());
+        <SubDex as OnInitialize<u64>>::on_initialize(System::block_number());
         <SubdexXcmp as OnInitialize<u64>>::on_initialize(System::block_number());
     }
 }
@@ -1,14 +1,38 @@
+mod batch_operations;
+mod dca;
 mod divest_liquidity;
+mod dynamic_fee;
+mod farming;
+mod fee_exemption;
+mod flash_swap;
+mod genesis_config;
 mod handle_downward_message;
 mod handle_xcmp_message;
 mod initialize_exchange;
 mod invest_liquidity;
+mod limit_order;
+mod liquidity_bootstrapping_pool;
+mod max_price_impact;
+mod oracle_deviation;
+mod pool_value_per_share;
+mod referral_fee;
+mod simulate_swap;
+mod stable_swap;
+mod stop_loss;
+mod swap_and_transfer_to_parachain_chain;
+mod swap_exact_to;
 mod transfer_balance_to_parachain_chain;
 mod transfer_balance_to_relay_chain;
+mod weighted_pool;
 
 pub use super::*;
 pub use crate::mock::*;
-use pallet_subdex::Exchange;
+use pallet_subdex::{
+    DcaOrderId, DexOperation, DynamicFee, Exchange, FarmingPosition, LimitOrderId, PoolCurve,
+    ScheduledOrder, StopLossOrderId, WeightSchedule,
+};
+use sp_core::U256;
+use sp_runtime::Permill;
 
 // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
 pub fn initialize_simple_exchange(
@@ -43,6 +67,102 @@ pub fn initialize_simple_exchange(
     ));
 }
 
+// Receive 10_0000 of main network currency and 6_0000 of parachain asset through xcmp and use them to initialize exchange.
+// Returns previosuly mapped parachain asset representation.
+pub fn setup_exchange() -> AssetId {
+    initialize_simple_exchange(FirstAccountId::get(), 10_0000, Some(5), 6_0000);
+
+    // previosuly mapped parachain asset representation
+    get_next_asset_id() - 1
+}
+
+// Price in fixed point representation, given in thousandths
+pub fn price(thousandths: u128) -> U256 {
+    U256::from(thousandths * 1_000_000_000_000_000)
+}
+
+// Initialize exchange with given main network currency and parachain asset pools and provide second account
+// with given amounts of both assets to operate with. Returns previosuly mapped parachain asset representation.
+pub fn setup_funded_exchange_with_pools(
+    main_network_currency_pool: Balance,
+    para_asset_pool: Balance,
+    main_network_currency_amount: Balance,
+    para_asset_amount: Balance,
+) -> AssetId {
+    initialize_simple_exchange(
+        FirstAccountId::get(),
+        main_network_currency_pool,
+        Some(5),
+        para_asset_pool,
+    );
+
+    if main_network_currency_amount > 0 {
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), main_network_currency_amount);
+    }
+
+    if para_asset_amount > 0 {
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            para_asset_amount,
+            Some(5),
+        );
+    }
+
+    // previosuly mapped parachain asset representation
+    get_next_asset_id() - 1
+}
+
+// Initialize exchange (same as setup_exchange) and provide second account with given amounts of both assets to operate with.
+// Returns previosuly mapped parachain asset representation.
+pub fn setup_funded_exchange(
+    main_network_currency_amount: Balance,
+    para_asset_amount: Balance,
+) -> AssetId {
+    setup_funded_exchange_with_pools(
+        10_0000,
+        6_0000,
+        main_network_currency_amount,
+        para_asset_amount,
+    )
+}
+
+// Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange with a given curve.
+// Returns previosuly mapped parachain asset representation.
+pub fn setup_exchange_with_curve(
+    para_asset_amount: Balance,
+    main_network_currency_amount: Balance,
+    curve: PoolCurve,
+) -> AssetId {
+    let asset_id = get_next_asset_id();
+
+    // Emulate xcmp message
+    emulate_xcmp_message(
+        FirstParaId::get(),
+        FirstAccountId::get(),
+        para_asset_amount,
+        Some(5),
+    );
+
+    // Emulate downward message
+    emulate_downward_message(FirstAccountId::get(), main_network_currency_amount);
+
+    // Initialize new exchange
+    assert_ok!(initialize_new_exchange_with_curve(
+        FirstAccountId::get(),
+        // previosuly mapped parachain asset representation
+        Asset::ParachainAsset(asset_id),
+        para_asset_amount,
+        Asset::MainNetworkCurrency,
+        main_network_currency_amount,
+        curve
+    ));
+
+    asset_id
+}
+
 // Subdex
 
 pub fn asset_balances(account_id: AccountId, asset_id: AssetId) -> Balance {
@@ -59,6 +179,24 @@ pub fn initialize_new_exchange(
     first_asset_amount: Balance,
     second_asset: Asset<AssetId>,
     second_asset_amount: Balance,
+) -> DispatchResult {
+    initialize_new_exchange_with_curve(
+        origin,
+        first_asset,
+        first_asset_amount,
+        second_asset,
+        second_asset_amount,
+        PoolCurve::ConstantProduct,
+    )
+}
+
+pub fn initialize_new_exchange_with_curve(
+    origin: AccountId,
+    first_asset: Asset<AssetId>,
+    first_asset_amount: Balance,
+    second_asset: Asset<AssetId>,
+    second_asset_amount: Balance,
+    curve: PoolCurve,
 ) -> DispatchResult {
     SubDex::initialize_exchange(
         Origin::signed(origin),
@@ -66,6 +204,25 @@ pub fn initialize_new_exchange(
         first_asset_amount,
         second_asset,
         second_asset_amount,
+        curve,
+    )
+}
+
+pub fn emulate_initialize_bootstrapping_exchange(
+    origin: AccountId,
+    first_asset: Asset<AssetId>,
+    first_asset_amount: Balance,
+    second_asset: Asset<AssetId>,
+    second_asset_amount: Balance,
+    weight_schedule: WeightSchedule<u64>,
+) -> DispatchResult {
+    SubDex::initialize_bootstrapping_exchange(
+        Origin::signed(origin),
+        first_asset,
+        first_asset_amount,
+        second_asset,
+        second_asset_amount,
+        weight_schedule,
     )
 }
 
@@ -78,6 +235,88 @@ pub fn emulate_invest_liquidity(
     SubDex::invest_liquidity(Origin::signed(origin), first_asset, second_asset, shares)
 }
 
+pub fn emulate_swap_exact_to(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_in_amount: Balance,
+    asset_out: Asset<AssetId>,
+    min_asset_out_amount: Balance,
+    receiver: AccountId,
+) -> DispatchResult {
+    SubDex::swap_exact_to(
+        Origin::signed(origin),
+        asset_in,
+        asset_in_amount,
+        asset_out,
+        min_asset_out_amount,
+        receiver,
+        None,
+    )
+}
+
+pub fn emulate_swap_exact_to_with_referrer(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_in_amount: Balance,
+    asset_out: Asset<AssetId>,
+    min_asset_out_amount: Balance,
+    receiver: AccountId,
+    referrer: AccountId,
+) -> DispatchResult {
+    SubDex::swap_exact_to(
+        Origin::signed(origin),
+        asset_in,
+        asset_in_amount,
+        asset_out,
+        min_asset_out_amount,
+        receiver,
+        Some(referrer),
+    )
+}
+
+pub fn emulate_flash_swap(
+    origin: AccountId,
+    asset_out: Asset<AssetId>,
+    asset_out_amount: Balance,
+    asset_in: Asset<AssetId>,
+    max_asset_in_amount: Balance,
+) -> DispatchResult {
+    SubDex::flash_swap(
+        Origin::signed(origin),
+        asset_out,
+        asset_out_amount,
+        asset_in,
+        max_asset_in_amount,
+        vec![],
+    )
+}
+
+// Flash swap, which swaps borrowed amount along the given path of assets inside flash swap receiver
+pub fn emulate_flash_swap_with_path(
+    origin: AccountId,
+    asset_out: Asset<AssetId>,
+    asset_out_amount: Balance,
+    asset_in: Asset<AssetId>,
+    max_asset_in_amount: Balance,
+    path: Vec<Asset<AssetId>>,
+) -> DispatchResult {
+    SubDex::flash_swap(
+        Origin::signed(origin),
+        asset_out,
+        asset_out_amount,
+        asset_in,
+        max_asset_in_amount,
+        path.encode(),
+    )
+}
+
+pub fn emulate_batch_operations(
+    origin: AccountId,
+    operations: Vec<DexOperation<AccountId, AssetId, Balance>>,
+) -> DispatchResult {
+    SubDex::batch_operations(Origin::signed(origin), operations)
+}
+
 pub fn emulate_divest_liquidity(
     origin: AccountId,
     first_asset: Asset<AssetId>,
@@ -96,6 +335,141 @@ pub fn emulate_divest_liquidity(
     )
 }
 
+pub fn emulate_set_reward_rate(
+    origin: Origin,
+    first_asset: Asset<AssetId>,
+    second_asset: Asset<AssetId>,
+    reward_asset: Asset<AssetId>,
+    reward_rate: Balance,
+) -> DispatchResult {
+    SubDex::set_reward_rate(origin, first_asset, second_asset, reward_asset, reward_rate)
+}
+
+pub fn emulate_fund_rewards(
+    origin: AccountId,
+    reward_asset: Asset<AssetId>,
+    amount: Balance,
+) -> DispatchResult {
+    SubDex::fund_rewards(Origin::signed(origin), reward_asset, amount)
+}
+
+pub fn emulate_claim_rewards(
+    origin: AccountId,
+    first_asset: Asset<AssetId>,
+    second_asset: Asset<AssetId>,
+) -> DispatchResult {
+    SubDex::claim_rewards(Origin::signed(origin), first_asset, second_asset)
+}
+
+pub fn emulate_set_max_price_impact(
+    origin: Origin,
+    first_asset: Asset<AssetId>,
+    second_asset: Asset<AssetId>,
+    max_price_impact: Option<Permill>,
+) -> DispatchResult {
+    SubDex::set_max_price_impact(origin, first_asset, second_asset, max_price_impact)
+}
+
+pub fn emulate_set_max_oracle_deviation(
+    origin: Origin,
+    max_oracle_deviation: Option<Permill>,
+) -> DispatchResult {
+    SubDex::set_max_oracle_deviation(origin, max_oracle_deviation)
+}
+
+pub fn emulate_set_dynamic_fee(origin: Origin, dynamic_fee: Option<DynamicFee>) -> DispatchResult {
+    SubDex::set_dynamic_fee(origin, dynamic_fee)
+}
+
+pub fn emulate_set_referral_share(origin: Origin, referral_share: Permill) -> DispatchResult {
+    SubDex::set_referral_share(origin, referral_share)
+}
+
+pub fn emulate_set_fee_exemption(
+    origin: Origin,
+    who: AccountId,
+    fee_discount: Option<Permill>,
+) -> DispatchResult {
+    SubDex::set_fee_exemption(origin, who, fee_discount)
+}
+
+pub fn emulate_create_dca_order(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_out: Asset<AssetId>,
+    amount_per_period: Balance,
+    period_blocks: u64,
+    periods: u32,
+    min_out_per_period: Balance,
+) -> DispatchResult {
+    SubDex::create_dca_order(
+        Origin::signed(origin),
+        asset_in,
+        asset_out,
+        amount_per_period,
+        period_blocks,
+        periods,
+        min_out_per_period,
+    )
+}
+
+pub fn emulate_cancel_dca_order(origin: AccountId, order_id: DcaOrderId) -> DispatchResult {
+    SubDex::cancel_dca_order(Origin::signed(origin), order_id)
+}
+
+pub fn emulate_create_limit_order(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_in_amount: Balance,
+    asset_out: Asset<AssetId>,
+    min_price: U256,
+    expiry: u64,
+) -> DispatchResult {
+    SubDex::create_limit_order(
+        Origin::signed(origin),
+        asset_in,
+        asset_in_amount,
+        asset_out,
+        min_price,
+        expiry,
+    )
+}
+
+pub fn emulate_cancel_limit_order(origin: AccountId, order_id: LimitOrderId) -> DispatchResult {
+    SubDex::cancel_limit_order(Origin::signed(origin), order_id)
+}
+
+pub fn emulate_create_stop_loss_order(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_in_amount: Balance,
+    asset_out: Asset<AssetId>,
+    trigger_price: U256,
+    min_asset_out_amount: Balance,
+    expiry: u64,
+) -> DispatchResult {
+    SubDex::create_stop_loss_order(
+        Origin::signed(origin),
+        asset_in,
+        asset_in_amount,
+        asset_out,
+        trigger_price,
+        min_asset_out_amount,
+        expiry,
+    )
+}
+
+pub fn emulate_cancel_stop_loss_order(
+    origin: AccountId,
+    order_id: StopLossOrderId,
+) -> DispatchResult {
+    SubDex::cancel_stop_loss_order(Origin::signed(origin), order_id)
+}
+
+pub fn set_timestamp(moment: u64) {
+    pallet_timestamp::Module::<Test>::set_timestamp(moment);
+}
+
 // Subdex Xcmp
 
 pub fn asset_id_exists(para_id: ParaId, asset_id: Option<AssetId>) -> bool {
@@ -148,3 +522,23 @@ pub fn emulate_transfer_asset_balance_to_parachain_chain(
         transfer_amount,
     )
 }
+
+pub fn emulate_swap_and_transfer_to_parachain_chain(
+    origin: AccountId,
+    asset_in: Asset<AssetId>,
+    asset_in_amount: Balance,
+    para_id: ParaId,
+    dest: AccountId,
+    para_asset_id: Option<AssetId>,
+    min_asset_out_amount: Balance,
+) -> DispatchResult {
+    SubdexXcmp::swap_and_transfer_to_parachain_chain(
+        Origin::signed(origin),
+        asset_in,
+        asset_in_amount,
+        para_id.into(),
+        dest,
+        para_asset_id,
+        min_asset_out_amount,
+    )
+}
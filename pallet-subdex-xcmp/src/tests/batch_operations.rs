@@ -0,0 +1,228 @@
+use super::*;
+
+#[test]
+fn batch_operations() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let shares_to_be_own = 1_000;
+
+        let asset_in_amount = 1_000;
+
+        let mut exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected exchange state after invest and swap performed one after another
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_costs(shares_to_be_own).unwrap();
+
+        exchange
+            .invest(
+                first_asset_cost,
+                second_asset_cost,
+                shares_to_be_own,
+                &SecondAccountId::get(),
+            )
+            .unwrap();
+
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        let joint_fee = exchange.calculate_joint_fee(asset_in_amount, None).unwrap();
+
+        exchange
+            .update_pools(swap_delta.first_asset_pool, swap_delta.second_asset_pool)
+            .unwrap();
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), first_asset_cost + asset_in_amount);
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            second_asset_cost,
+            Some(5),
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Invest liquidity and swap main network currency to parachain asset in a single batch
+        assert_ok!(emulate_batch_operations(
+            SecondAccountId::get(),
+            vec![
+                DexOperation::Invest(
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                    shares_to_be_own
+                ),
+                DexOperation::Swap(
+                    Asset::MainNetworkCurrency,
+                    asset_in_amount,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                    swap_delta.amount,
+                    SecondAccountId::get(),
+                    None
+                ),
+            ]
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure both operations were performed against the same exchange state
+        assert_eq!(
+            exchange,
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            )
+        );
+
+        assert_eq!(Balances::free_balance(SecondAccountId::get()), 0);
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            swap_delta.amount
+        );
+
+        let exchanged_event = get_subdex_test_event(pallet_subdex::RawEvent::Exchanged(
+            SecondAccountId::get(),
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount,
+            joint_fee,
+            Some(treasury_fee),
+            None,
+        ));
+
+        // Last event checked
+        assert_event_success(
+            exchanged_event,
+            // additional events emitted when invest and Currency slash() performed and both pool updates
+            number_of_events_before_call + 5,
+        );
+    })
+}
+
+#[test]
+fn batch_operations_rolled_back() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let shares_to_be_own = 1_000;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_costs(shares_to_be_own).unwrap();
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), first_asset_cost);
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            second_asset_cost,
+            Some(5),
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to invest liquidity and then swap, having no assets left to be swapped
+        let batch_operations_result = emulate_batch_operations(
+            SecondAccountId::get(),
+            vec![
+                DexOperation::Invest(
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                    shares_to_be_own,
+                ),
+                DexOperation::Swap(
+                    Asset::MainNetworkCurrency,
+                    1_000,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                    0,
+                    SecondAccountId::get(),
+                    None,
+                ),
+            ],
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            batch_operations_result,
+            pallet_subdex::Error::<Test>::InsufficientMainNetworkAssetAmount,
+            number_of_events_before_call,
+        );
+
+        // Ensure whole batch was rolled back
+        assert_eq!(
+            exchange,
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            )
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            first_asset_cost
+        );
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            second_asset_cost
+        );
+    })
+}
+
+#[test]
+fn batch_operations_too_many() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        let swap = DexOperation::Swap(
+            Asset::MainNetworkCurrency,
+            1_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+            None,
+        );
+
+        // Make an attempt to perform more operations, than allowed per batch
+        let batch_operations_result = emulate_batch_operations(
+            SecondAccountId::get(),
+            vec![swap; MaxBatchOperations::get() as usize + 1],
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            batch_operations_result,
+            pallet_subdex::Error::<Test>::TooManyBatchOperations,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,415 @@
+use super::*;
+
+// Create order to swap 1_000 of parachain asset to main network currency every 2 blocks, 3 times
+fn place_dca_order(dex_para_asset_id: AssetId, min_out_per_period: Balance) {
+    assert_ok!(emulate_create_dca_order(
+        SecondAccountId::get(),
+        Asset::ParachainAsset(dex_para_asset_id),
+        Asset::MainNetworkCurrency,
+        1_000,
+        2,
+        3,
+        min_out_per_period
+    ));
+}
+
+#[test]
+fn create_dca_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        place_dca_order(dex_para_asset_id, 0);
+
+        // Runtime tested state after call
+
+        // Ensure funds for all periods and order deposit were reserved
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            7_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            3_000
+        );
+
+        assert_eq!(
+            Balances::reserved_balance(SecondAccountId::get()),
+            OrderDeposit::get()
+        );
+
+        let order = SubDex::dca_orders(0).unwrap();
+
+        assert_eq!(order.owner, SecondAccountId::get());
+        assert_eq!(order.periods_left, 3);
+        assert_eq!(order.next_execution, System::block_number() + 1);
+        assert_eq!(order.deposit, OrderDeposit::get());
+
+        assert_eq!(SubDex::next_dca_order_id(), 1);
+
+        // Ensure order was pushed to the scheduled orders queue
+        assert_eq!(
+            SubDex::order_queue(System::block_number() + 1, 0),
+            Some(ScheduledOrder::Dca(0))
+        );
+
+        assert_eq!(SubDex::queued_orders_count(), 1);
+
+        let dca_order_created_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::DcaOrderCreated(
+                0,
+                SecondAccountId::get(),
+                Asset::ParachainAsset(dex_para_asset_id),
+                Asset::MainNetworkCurrency,
+                1_000,
+                2,
+                3,
+            ));
+
+        // Last event checked (order deposit reserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            dca_order_created_event
+        );
+    })
+}
+
+#[test]
+fn dca_order_executed_each_period() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_dca_order(dex_para_asset_id, 0);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta for the first period
+        let (swap_delta, _, _) = exchange
+            .calculate_second_to_first_asset_swap(1_000, None, None)
+            .unwrap();
+
+        let free_balance_before_block = Balances::free_balance(SecondAccountId::get());
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure the first period swap output was credited to the owner
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            free_balance_before_block + swap_delta.amount
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            2_000
+        );
+
+        let order = SubDex::dca_orders(0).unwrap();
+
+        assert_eq!(order.periods_left, 2);
+        assert_eq!(order.next_execution, System::block_number() + 2);
+
+        let dca_order_executed_event = get_subdex_test_event(
+            pallet_subdex::RawEvent::DcaOrderExecuted(0, 1_000, swap_delta.amount),
+        );
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            dca_order_executed_event
+        );
+
+        // Ensure nothing is executed between periods
+        run_to_block(System::block_number() + 1);
+
+        assert_eq!(SubDex::dca_orders(0).unwrap().periods_left, 2);
+
+        run_to_block(System::block_number() + 3);
+
+        // Ensure order was completed, all reserved funds were swapped and order deposit was refunded
+        assert!(SubDex::dca_orders(0).is_none());
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        assert_eq!(SubDex::queued_orders_count(), 0);
+
+        let dca_order_completed_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::DcaOrderCompleted(0));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            dca_order_completed_event
+        );
+    })
+}
+
+#[test]
+fn dca_orders_execution_cap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        let orders_count = MaxScheduledOrdersPerBlock::get() as DcaOrderId + 1;
+
+        // Create more orders, than allowed to be processed per block
+        for _ in 0..orders_count {
+            place_dca_order(dex_para_asset_id, 0);
+        }
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure only capped number of orders was executed in order of scheduling, the rest was left in the queue
+        for order_id in 0..orders_count - 1 {
+            assert_eq!(SubDex::dca_orders(order_id).unwrap().periods_left, 2);
+        }
+
+        assert_eq!(
+            SubDex::dca_orders(orders_count - 1).unwrap().periods_left,
+            3
+        );
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure left order was executed first the next block
+        assert!((0..orders_count)
+            .all(|order_id| SubDex::dca_orders(order_id).unwrap().periods_left == 2));
+
+        let left_order = SubDex::dca_orders(orders_count - 1).unwrap();
+
+        assert_eq!(left_order.next_execution, System::block_number() + 2);
+
+        // Ensure executed order was pushed to the queue at its next execution block
+        assert_eq!(
+            SubDex::order_queue(left_order.next_execution, 0),
+            Some(ScheduledOrder::Dca(orders_count - 1))
+        );
+
+        assert_eq!(SubDex::queued_orders_count(), orders_count as u32);
+    })
+}
+
+#[test]
+fn dca_order_period_skipped() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Create order with min asset out amount, which can not be satisfied
+        place_dca_order(dex_para_asset_id, 10_000);
+
+        // Events number before tested block
+        let number_of_events_before_block = System::events().len();
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure period amount was refunded to the owner
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            8_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            2_000
+        );
+
+        assert_eq!(SubDex::dca_orders(0).unwrap().periods_left, 2);
+
+        let dca_order_period_skipped_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::DcaOrderPeriodSkipped(0, 1_000));
+
+        // Last event checked
+        assert_event_success(
+            dca_order_period_skipped_event,
+            number_of_events_before_block + 1,
+        );
+    })
+}
+
+#[test]
+fn cancel_dca_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_dca_order(dex_para_asset_id, 0);
+
+        run_to_block(System::block_number() + 1);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_cancel_dca_order(SecondAccountId::get(), 0));
+
+        // Runtime tested state after call
+
+        // Ensure remaining funds and order deposit were refunded to the owner
+        assert!(SubDex::dca_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            9_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        let dca_order_cancelled_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::DcaOrderCancelled(0, 2_000));
+
+        // Last event checked (order deposit unreserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            dca_order_cancelled_event
+        );
+
+        let number_of_events_after_call = System::events().len();
+
+        // Ensure cancelled order is not executed anymore and its queue entry is skipped
+        run_to_block(System::block_number() + 4);
+
+        assert_eq!(System::events().len(), number_of_events_after_call);
+
+        assert_eq!(SubDex::queued_orders_count(), 0);
+    })
+}
+
+#[test]
+fn cancel_dca_order_not_owner() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_dca_order(dex_para_asset_id, 0);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to cancel order, created by another account
+        let cancel_dca_order_result = emulate_cancel_dca_order(FirstAccountId::get(), 0);
+
+        // Failure checked
+        assert_subdex_failure(
+            cancel_dca_order_result,
+            pallet_subdex::Error::<Test>::NotDcaOrderOwner,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_dca_order_invalid_schedule() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to create order without periods
+        let create_dca_order_result = emulate_create_dca_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000,
+            2,
+            0,
+            0,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_dca_order_result,
+            pallet_subdex::Error::<Test>::InvalidDcaSchedule,
+            number_of_events_before_call,
+        );
+
+        // Make an attempt to create order, which periods span more blocks, than max order lifetime
+        let create_dca_order_result = emulate_create_dca_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000,
+            MaxOrderLifetime::get(),
+            2,
+            0,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_dca_order_result,
+            pallet_subdex::Error::<Test>::InvalidDcaSchedule,
+            number_of_events_before_call,
+        );
+
+        // Make an attempt to create order with period blocks, causing block number overflow
+        let create_dca_order_result = emulate_create_dca_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000,
+            u64::max_value(),
+            2,
+            0,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_dca_order_result,
+            pallet_subdex::Error::<Test>::InvalidDcaSchedule,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_dca_order_insufficient_balance() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to reserve more, than account owns: 4_000 * 3 > 10_000
+        let create_dca_order_result = emulate_create_dca_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            4_000,
+            2,
+            3,
+            0,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_dca_order_result,
+            pallet_subdex::Error::<Test>::InsufficientParachainAssetAmount,
+            number_of_events_before_call,
+        );
+    })
+}
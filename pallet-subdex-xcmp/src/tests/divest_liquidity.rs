@@ -28,10 +28,11 @@ fn divest_liquidity() {
             Asset::ParachainAsset(get_next_asset_id() - 1),
         );
 
-        let shares = exchange.total_shares;
+        let shares = exchange.shares(&FirstAccountId::get());
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
-        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
 
         // Runtime tested state before call
 
@@ -67,6 +68,7 @@ fn divest_liquidity() {
             main_network_currency_transfer_amount,
             para_asset_transfer_amount,
             FirstAccountId::get(),
+            PoolCurve::ConstantProduct,
         )
         .unwrap();
 
@@ -90,13 +92,161 @@ fn divest_liquidity() {
             Asset::MainNetworkCurrency,
             Asset::ParachainAsset(dex_para_asset_id),
             shares,
+            first_asset_cost,
+            second_asset_cost,
         ));
 
         // Last event checked
         assert_event_success(
             exchange_invested_event,
-            // additional events emitted when Currency deposit_creating() method performed
-            number_of_events_before_call + 3,
+            // additional events emitted when Currency deposit_creating() method performed and pool updated
+            number_of_events_before_call + 4,
+        );
+    })
+}
+
+#[test]
+fn divest_liquidity_large_pools() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+        // Pools exceed 1e18, so that pools and shares products exceed u128 range.
+
+        let main_network_currency_transfer_amount = 1_000_000_000_000_000_000_000;
+
+        let para_asset_transfer_amount = 600_000_000_000_000_000_000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Divest half of owned shares
+        let shares = exchange.shares(&FirstAccountId::get()) / 2;
+
+        // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
+
+        // Ensure divested amounts are proportional to pools (rounded down)
+        assert!(first_asset_cost <= main_network_currency_transfer_amount / 2);
+        assert!(first_asset_cost > main_network_currency_transfer_amount / 2 * 99 / 100);
+        assert!(second_asset_cost <= para_asset_transfer_amount / 2);
+        assert!(second_asset_cost > para_asset_transfer_amount / 2 * 99 / 100);
+
+        assert_ok!(emulate_divest_liquidity(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares,
+            first_asset_cost,
+            second_asset_cost
+        ));
+
+        // Ensure both main network and parachain asset balances were successfully divested
+        assert_eq!(
+            asset_balances(FirstAccountId::get(), dex_para_asset_id),
+            second_asset_cost
+        );
+
+        assert_eq!(
+            Balances::free_balance(FirstAccountId::get()),
+            first_asset_cost
+        );
+
+        let exchange_after_divest = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_divest.first_asset_pool(),
+            main_network_currency_transfer_amount - first_asset_cost
+        );
+        assert_eq!(
+            exchange_after_divest.second_asset_pool(),
+            para_asset_transfer_amount - second_asset_cost
+        );
+    })
+}
+
+#[test]
+fn divest_liquidity_min_liquidity_locked() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let shares = exchange.shares(&FirstAccountId::get());
+
+        // Min liquidity shares (1_000 for 128 bit balances) are not credited to the exchange initializer
+        assert_eq!(exchange.total_shares - shares, 1_000);
+
+        // Divest all liquidity, owned by exchange initializer
+        assert_ok!(emulate_divest_liquidity(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares,
+            0,
+            0
+        ));
+
+        // Runtime tested state after call
+
+        let exchange_after_divest_performed = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure exchange was not drained to an empty state, min liquidity shares stay locked
+        assert_eq!(exchange_after_divest_performed.total_shares, 1_000);
+        assert!(exchange_after_divest_performed.first_asset_pool() > 0);
+        assert!(exchange_after_divest_performed.second_asset_pool() > 0);
+
+        // Ensure exchange can not be reinitialized
+        assert!(SubDex::ensure_exchange_exists(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id)
+        )
+        .is_ok());
+
+        // Ensure zero-share entry was removed from the exchange
+        assert_eq!(
+            exchange_after_divest_performed.ensure_burned_shares(&FirstAccountId::get(), 1),
+            Err(pallet_subdex::Error::<Test>::DoesNotOwnShare.into())
         );
     })
 }
@@ -126,10 +276,11 @@ fn divest_liquidity_invalid_exchange() {
             Asset::ParachainAsset(get_next_asset_id() - 1),
         );
 
-        let shares = exchange.total_shares;
+        let shares = exchange.shares(&FirstAccountId::get());
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
-        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
 
         // Runtime tested state before call
 
@@ -235,10 +386,11 @@ fn divest_liquidity_does_not_own_share() {
             Asset::ParachainAsset(dex_para_asset_id),
         );
 
-        let shares = exchange.total_shares;
+        let shares = exchange.shares(&FirstAccountId::get());
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
-        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
 
         // Runtime tested state before call
 
@@ -329,7 +481,7 @@ fn divest_liquidity_insufficient_shares() {
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
         let (first_asset_cost, second_asset_cost) = exchange_after_invest_performed
-            .calculate_costs(total_shares)
+            .calculate_divest_amounts(total_shares)
             .unwrap();
 
         // Runtime tested state before call
@@ -384,10 +536,11 @@ fn divest_liquidity_first_asset_amount_below_expectation() {
             Asset::ParachainAsset(dex_para_asset_id),
         );
 
-        let shares = exchange.total_shares;
+        let shares = exchange.shares(&FirstAccountId::get());
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
-        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
 
         // Runtime tested state before call
 
@@ -441,10 +594,11 @@ fn divest_liquidity_second_asset_amount_below_expectation() {
             Asset::ParachainAsset(dex_para_asset_id),
         );
 
-        let shares = exchange.total_shares;
+        let shares = exchange.shares(&FirstAccountId::get());
 
         // Calculate an amount of both assets, needed to be divested, to extract an exact amount of shares.
-        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares).unwrap();
 
         // Runtime tested state before call
 
@@ -469,3 +623,62 @@ fn divest_liquidity_second_asset_amount_below_expectation() {
         )
     })
 }
+
+#[test]
+fn divest_liquidity_positions_updated() {
+    with_test_externalities(|| {
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(FirstAccountId::get(), 10_0000, Some(5), 6_0000);
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let shares = exchange.shares(&FirstAccountId::get());
+
+        let (first_asset_amount, second_asset_amount) =
+            exchange.calculate_divest_amounts(shares).unwrap();
+
+        // Ensure exchange initializer position indexed
+        assert_eq!(
+            SubDex::positions_of(&FirstAccountId::get()),
+            vec![(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+                shares,
+                first_asset_amount,
+                second_asset_amount
+            )]
+        );
+
+        // Divest part of available liquidity
+        assert_ok!(emulate_divest_liquidity(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares / 2,
+            0,
+            0
+        ));
+
+        // Ensure position still indexed
+        assert_eq!(SubDex::positions_of(&FirstAccountId::get()).len(), 1);
+
+        // Divest remaining liquidity
+        assert_ok!(emulate_divest_liquidity(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares - shares / 2,
+            0,
+            0
+        ));
+
+        // Ensure position removed from index, once all shares burned
+        assert_eq!(SubDex::positions_of(&FirstAccountId::get()), vec![]);
+    })
+}
@@ -0,0 +1,184 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+// Dynamic fee, scaling from 0.1% to 1% as volatility grows up to 10%
+fn dynamic_fee() -> DynamicFee {
+    DynamicFee::new(
+        Permill::from_parts(1_000),
+        Permill::from_percent(1),
+        Permill::from_percent(10),
+    )
+}
+
+#[test]
+fn set_dynamic_fee() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_set_dynamic_fee(Origin::root(), Some(dynamic_fee())));
+
+        // Runtime tested state after call
+
+        assert_eq!(SubDex::dynamic_fee(), Some(dynamic_fee()));
+
+        let dynamic_fee_set_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::DynamicFeeSet(Some(dynamic_fee())));
+
+        // Last event checked
+        assert_event_success(dynamic_fee_set_event, number_of_events_before_call + 1);
+    })
+}
+
+#[test]
+fn swap_exact_to_dynamic_fee_scales_with_volatility() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        assert_ok!(emulate_set_dynamic_fee(Origin::root(), Some(dynamic_fee())));
+
+        // Ensure min fee rate is applied, while price is stable
+        assert_eq!(
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id)
+            )
+            .calculate_joint_fee(10_000, None)
+            .unwrap(),
+            10
+        );
+
+        // Complete the first twap period and move price: (110_000 / 54_550) / (100_000 / 60_000) ~ 1.21
+        set_timestamp(TwapPeriod::get());
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get()
+        ));
+
+        // Complete the second twap period, so that volatility is measured between both average prices
+        set_timestamp(2 * TwapPeriod::get());
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            1_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get()
+        ));
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure volatility exceeds the threshold, so that max fee rate is applied
+        assert!(exchange.volatility > Permill::from_percent(20));
+
+        let asset_in_amount = 1_000;
+
+        assert_eq!(
+            exchange.calculate_joint_fee(asset_in_amount, None).unwrap(),
+            10
+        );
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            Some(5),
+        );
+
+        // Calculate expected swap delta and treasury fee
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_second_to_first_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            FirstAccountId::get()
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure treasury fee is taken from the dynamic joint fee
+        assert_eq!(treasury_fee, 2);
+
+        let exchanged_event = get_subdex_test_event(pallet_subdex::RawEvent::Exchanged(
+            SecondAccountId::get(),
+            FirstAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            10,
+            Some(treasury_fee),
+            None,
+        ));
+
+        // Last event checked
+        assert_event_success(
+            exchanged_event,
+            // additional events emitted when Currency deposit_creating() method performed and pool updated
+            number_of_events_before_call + 4,
+        );
+    })
+}
+
+#[test]
+fn set_dynamic_fee_invalid() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to set min fee rate above max fee rate
+        let set_dynamic_fee_result = emulate_set_dynamic_fee(
+            Origin::root(),
+            Some(DynamicFee::new(
+                Permill::from_percent(2),
+                Permill::from_percent(1),
+                Permill::from_percent(10),
+            )),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            set_dynamic_fee_result,
+            pallet_subdex::Error::<Test>::InvalidDynamicFee,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn set_dynamic_fee_bad_origin() {
+    with_test_externalities(|| {
+        // Make an attempt to set dynamic fee, using regular account
+        let set_dynamic_fee_result =
+            emulate_set_dynamic_fee(Origin::signed(FirstAccountId::get()), Some(dynamic_fee()));
+
+        // Failure checked
+        assert_err!(set_dynamic_fee_result, DispatchError::BadOrigin);
+    })
+}
@@ -0,0 +1,338 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+// Set main network currency reward rate for exchange and fund rewards account
+fn setup_farm(dex_para_asset_id: AssetId, reward_rate: Balance) {
+    assert_ok!(emulate_set_reward_rate(
+        Origin::root(),
+        Asset::MainNetworkCurrency,
+        Asset::ParachainAsset(dex_para_asset_id),
+        Asset::MainNetworkCurrency,
+        reward_rate
+    ));
+
+    // Emulate downward message
+    emulate_downward_message(SecondAccountId::get(), 10_0000);
+
+    assert_ok!(emulate_fund_rewards(
+        SecondAccountId::get(),
+        Asset::MainNetworkCurrency,
+        5_0000
+    ));
+}
+
+// Calculate rewards, accumulated by given account shares up to the current block
+fn pending_rewards(dex_para_asset_id: AssetId, account_id: AccountId) -> Balance {
+    let (first_asset, second_asset) = (
+        Asset::MainNetworkCurrency,
+        Asset::ParachainAsset(dex_para_asset_id),
+    );
+
+    let exchange = dex_exchanges(first_asset, second_asset);
+    let shares = exchange.shares(&account_id);
+
+    let mut farm = SubDex::farms(first_asset, second_asset).unwrap();
+    farm.accumulate(exchange.total_shares, System::block_number())
+        .unwrap();
+
+    let mut position = SubDex::farming_positions((first_asset, second_asset), account_id);
+    farm.settle(&mut position, shares, shares).unwrap();
+
+    position.unclaimed_rewards
+}
+
+#[test]
+fn set_reward_rate() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_set_reward_rate(
+            Origin::root(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            Asset::MainNetworkCurrency,
+            1_000
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure farm was created for adjusted assets order
+        let farm = SubDex::farms(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        )
+        .unwrap();
+
+        assert_eq!(farm.reward_asset, Asset::MainNetworkCurrency);
+        assert_eq!(farm.reward_rate, 1_000);
+
+        let reward_rate_set_event = get_subdex_test_event(pallet_subdex::RawEvent::RewardRateSet(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000,
+        ));
+
+        // Last event checked
+        assert_event_success(reward_rate_set_event, number_of_events_before_call + 1);
+    })
+}
+
+#[test]
+fn set_reward_rate_bad_origin() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Make an attempt to set reward rate, using regular account
+        let set_reward_rate_result = emulate_set_reward_rate(
+            Origin::signed(FirstAccountId::get()),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000,
+        );
+
+        // Failure checked
+        assert_err!(set_reward_rate_result, DispatchError::BadOrigin);
+    })
+}
+
+#[test]
+fn set_reward_rate_invalid_reward_asset() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        setup_farm(dex_para_asset_id, 1_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to change farm reward asset
+        let set_reward_rate_result = emulate_set_reward_rate(
+            Origin::root(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            set_reward_rate_result,
+            pallet_subdex::Error::<Test>::InvalidRewardAsset,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn claim_rewards() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        setup_farm(dex_para_asset_id, 1_000);
+
+        run_to_block(11);
+
+        let rewards = pending_rewards(dex_para_asset_id, FirstAccountId::get());
+
+        // Ensure almost all distributed rewards accrued to the only liquidity provider (except locked min liquidity share)
+        assert!(rewards > 9_800 && rewards <= 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_claim_rewards(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id)
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure rewards paid from rewards account
+        assert_eq!(Balances::free_balance(FirstAccountId::get()), rewards);
+
+        assert_eq!(
+            Balances::free_balance(SubDex::account_id()),
+            5_0000 - rewards
+        );
+
+        // Ensure nothing left to claim
+        assert_eq!(pending_rewards(dex_para_asset_id, FirstAccountId::get()), 0);
+
+        let rewards_claimed_event = get_subdex_test_event(pallet_subdex::RawEvent::RewardsClaimed(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            rewards,
+        ));
+
+        // Last event checked
+        assert_event_success(
+            rewards_claimed_event,
+            // additional events emitted when Currency deposit_creating() method performed
+            number_of_events_before_call + 3,
+        );
+    })
+}
+
+#[test]
+fn invest_and_divest_settle_rewards() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        setup_farm(dex_para_asset_id, 1_000);
+
+        run_to_block(6);
+
+        let shares = 10_000;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            second_asset_cost,
+            Some(5),
+        );
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), first_asset_cost);
+
+        let first_account_rewards_before_invest =
+            pending_rewards(dex_para_asset_id, FirstAccountId::get());
+
+        assert_ok!(emulate_invest_liquidity(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares
+        ));
+
+        // Ensure new liquidity provider does not receive rewards, accumulated before invest
+        assert_eq!(
+            pending_rewards(dex_para_asset_id, SecondAccountId::get()),
+            0
+        );
+
+        // Ensure other liquidity providers rewards are not affected
+        assert_eq!(
+            pending_rewards(dex_para_asset_id, FirstAccountId::get()),
+            first_account_rewards_before_invest
+        );
+
+        run_to_block(11);
+
+        let second_account_rewards = pending_rewards(dex_para_asset_id, SecondAccountId::get());
+
+        // Ensure rewards are shared proportionally: 5 blocks * 1000 * shares / total shares
+        assert!(
+            second_account_rewards > 0
+                && second_account_rewards < 5_000 * shares / exchange.total_shares
+        );
+
+        assert_ok!(emulate_divest_liquidity(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares,
+            0,
+            0
+        ));
+
+        // Ensure pending rewards were settled on divest, though no shares left
+        assert_eq!(
+            SubDex::farming_positions(
+                (
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(dex_para_asset_id)
+                ),
+                SecondAccountId::get()
+            ),
+            FarmingPosition {
+                reward_debt: 0,
+                unclaimed_rewards: second_account_rewards,
+            }
+        );
+    })
+}
+
+#[test]
+fn claim_rewards_farm_not_exists() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to claim rewards, when farm was not set
+        let claim_rewards_result = emulate_claim_rewards(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            claim_rewards_result,
+            pallet_subdex::Error::<Test>::FarmNotExists,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn claim_rewards_insufficient_reward_funds() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Set reward rate without funding rewards account
+        assert_ok!(emulate_set_reward_rate(
+            Origin::root(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            1_000
+        ));
+
+        run_to_block(11);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to claim rewards
+        let claim_rewards_result = emulate_claim_rewards(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            claim_rewards_result,
+            pallet_subdex::Error::<Test>::InsufficientRewardFunds,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,164 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+#[test]
+fn set_fee_exemption() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_set_fee_exemption(
+            Origin::root(),
+            SecondAccountId::get(),
+            Some(Permill::from_percent(50))
+        ));
+
+        // Runtime tested state after call
+
+        assert_eq!(
+            SubDex::fee_exemptions(SecondAccountId::get()),
+            Some(Permill::from_percent(50))
+        );
+
+        let fee_exemption_set_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::FeeExemptionSet(
+                SecondAccountId::get(),
+                Some(Permill::from_percent(50)),
+            ));
+
+        // Last event checked
+        assert_event_success(fee_exemption_set_event, number_of_events_before_call + 1);
+    })
+}
+
+#[test]
+fn fee_discount_applied_to_joint_fee() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        assert_ok!(emulate_set_fee_exemption(
+            Origin::root(),
+            SecondAccountId::get(),
+            Some(Permill::from_percent(50))
+        ));
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure half of joint fee is charged: 10_000 * 3 / 1000 * 50%
+        assert_eq!(
+            exchange
+                .calculate_joint_fee(10_000, Some(&SecondAccountId::get()))
+                .unwrap(),
+            15
+        );
+
+        // Ensure other accounts pay full joint fee
+        assert_eq!(
+            exchange
+                .calculate_joint_fee(10_000, Some(&FirstAccountId::get()))
+                .unwrap(),
+            30
+        );
+
+        // Ensure discount is reflected in swap simulation
+        let swap_simulation = SubDex::simulate_swap(
+            Asset::ParachainAsset(dex_para_asset_id),
+            10_000,
+            Asset::MainNetworkCurrency,
+            Some(SecondAccountId::get()),
+        )
+        .unwrap();
+
+        assert_eq!(swap_simulation.treasury_fee, Some(3));
+        assert_eq!(swap_simulation.lp_fee, 12);
+    })
+}
+
+#[test]
+fn swap_exact_to_fee_exempt() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let para_asset_id = Some(5);
+
+        let asset_in_amount = 10_000;
+
+        assert_ok!(emulate_set_fee_exemption(
+            Origin::root(),
+            SecondAccountId::get(),
+            Some(Permill::from_percent(100))
+        ));
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            para_asset_id,
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta for exempted account
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_second_to_first_asset_swap(
+                asset_in_amount,
+                Some(&SecondAccountId::get()),
+                None,
+            )
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        // Ensure exemption shows up in quoted swap delta
+        let (regular_swap_delta, _, _) = exchange
+            .calculate_second_to_first_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        assert!(swap_delta.amount > regular_swap_delta.amount);
+        assert_eq!(treasury_fee, 0);
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure no fee was charged
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            swap_delta.amount
+        );
+
+        assert_eq!(
+            asset_balances(TreasuryAccountId::get(), dex_para_asset_id),
+            0
+        );
+    })
+}
+
+#[test]
+fn set_fee_exemption_bad_origin() {
+    with_test_externalities(|| {
+        // Make an attempt to set fee exemption, using regular account
+        let set_fee_exemption_result = emulate_set_fee_exemption(
+            Origin::signed(SecondAccountId::get()),
+            SecondAccountId::get(),
+            Some(Permill::from_percent(100)),
+        );
+
+        // Failure checked
+        assert_err!(set_fee_exemption_result, DispatchError::BadOrigin);
+    })
+}
@@ -0,0 +1,369 @@
+use super::*;
+
+#[test]
+fn flash_swap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let asset_out_amount = 500;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 1_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate main network currency amount, needed to repay flash swap
+        let asset_in_amount = exchange
+            .calculate_first_asset_flash_swap_repayment(asset_out_amount, None)
+            .unwrap();
+
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Ensure repayment restores invariant plus fee
+        assert!(swap_delta.amount >= asset_out_amount);
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Borrow parachain asset and repay it with main network currency
+        assert_ok!(emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_out_amount,
+            Asset::MainNetworkCurrency,
+            asset_in_amount
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure borrowed amount credited and repayment slashed
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            asset_out_amount
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            1_000 - asset_in_amount
+        );
+
+        // Ensure exchange pools updated successfully
+        let exchange_after_flash_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_flash_swap.first_asset_pool(),
+            exchange.first_asset_pool() + asset_in_amount - treasury_fee
+        );
+
+        assert_eq!(
+            exchange_after_flash_swap.second_asset_pool(),
+            exchange.second_asset_pool() - asset_out_amount
+        );
+
+        let flash_swapped_event = get_subdex_test_event(pallet_subdex::RawEvent::FlashSwapped(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_out_amount,
+            Some(treasury_fee),
+        ));
+
+        // Last event checked (additional event emitted when pool updated)
+        assert_event_success(flash_swapped_event, number_of_events_before_call + 2);
+    })
+}
+
+#[test]
+fn flash_swap_not_repaid() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to borrow parachain asset, having nothing to repay flash swap with
+        let flash_swap_result = emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            500,
+            Asset::MainNetworkCurrency,
+            1_000,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            flash_swap_result,
+            pallet_subdex::Error::<Test>::InsufficientMainNetworkAssetAmount,
+            number_of_events_before_call,
+        );
+
+        // Ensure flash swap rolled back
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        assert_eq!(
+            exchange,
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            )
+        );
+    })
+}
+
+#[test]
+fn flash_swap_repayment_amount_above_expectation() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let asset_out_amount = 500;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 1_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate main network currency amount, needed to repay flash swap
+        let asset_in_amount = exchange
+            .calculate_first_asset_flash_swap_repayment(asset_out_amount, None)
+            .unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to borrow parachain asset, expecting lower repayment amount
+        let flash_swap_result = emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_out_amount,
+            Asset::MainNetworkCurrency,
+            asset_in_amount - 1,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            flash_swap_result,
+            pallet_subdex::Error::<Test>::RepaymentAmountAboveExpectation,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn flash_swap_insufficient_pool() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to borrow whole parachain asset pool
+        let flash_swap_result = emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            exchange.second_asset_pool(),
+            Asset::MainNetworkCurrency,
+            Balance::max_value(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            flash_swap_result,
+            pallet_subdex::Error::<Test>::InsufficientPool,
+            number_of_events_before_call,
+        );
+    })
+}
+
+// Initialize main network currency / first parachain asset (spot price 10 / 6), main network currency / second parachain asset (spot price 20 / 6)
+// and first parachain asset / second parachain asset (spot price 1) exchanges, so that arbitrage between them is possible
+fn setup_arbitrage_exchanges() -> (AssetId, AssetId) {
+    let first_dex_para_asset_id = setup_exchange();
+
+    initialize_simple_exchange(FirstAccountId::get(), 20_0000, Some(6), 6_0000);
+
+    let second_dex_para_asset_id = get_next_asset_id() - 1;
+
+    // Emulate xcmp messages
+    emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 6_0000, Some(5));
+    emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 6_0000, Some(6));
+
+    assert_ok!(initialize_new_exchange(
+        FirstAccountId::get(),
+        Asset::ParachainAsset(first_dex_para_asset_id),
+        6_0000,
+        Asset::ParachainAsset(second_dex_para_asset_id),
+        6_0000
+    ));
+
+    (first_dex_para_asset_id, second_dex_para_asset_id)
+}
+
+#[test]
+fn flash_swap_repaid_with_swap_proceeds() {
+    with_test_externalities(|| {
+        let (first_dex_para_asset_id, second_dex_para_asset_id) = setup_arbitrage_exchanges();
+
+        let asset_out_amount = 1_000;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(first_dex_para_asset_id),
+        );
+
+        // Calculate main network currency amount, needed to repay flash swap
+        let asset_in_amount = exchange
+            .calculate_first_asset_flash_swap_repayment(asset_out_amount, None)
+            .unwrap();
+
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        // Calculate proceeds of borrowed amount swap: first parachain asset -> second parachain asset -> main network currency
+        let (para_assets_swap_delta, _, _) = dex_exchanges(
+            Asset::ParachainAsset(first_dex_para_asset_id),
+            Asset::ParachainAsset(second_dex_para_asset_id),
+        )
+        .calculate_first_to_second_asset_swap(asset_out_amount, None, None)
+        .unwrap();
+
+        let (main_network_currency_swap_delta, _, _) = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(second_dex_para_asset_id),
+        )
+        .calculate_second_to_first_asset_swap(para_assets_swap_delta.amount, None, None)
+        .unwrap();
+
+        // Ensure arbitrage proceeds cover repayment
+        assert!(main_network_currency_swap_delta.amount > asset_in_amount);
+
+        // Borrow first parachain asset, swap it inside flash swap receiver and repay flash swap with proceeds
+        assert_ok!(emulate_flash_swap_with_path(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(first_dex_para_asset_id),
+            asset_out_amount,
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            vec![
+                Asset::ParachainAsset(second_dex_para_asset_id),
+                Asset::MainNetworkCurrency
+            ]
+        ));
+
+        // Ensure borrowed amount was swapped and arbitrage profit left on borrower account
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), first_dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            main_network_currency_swap_delta.amount - asset_in_amount
+        );
+
+        // Ensure flash swapped exchange pools updated after repayment
+        let exchange_after_flash_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(first_dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_flash_swap.first_asset_pool(),
+            swap_delta.first_asset_pool
+        );
+
+        assert_eq!(
+            exchange_after_flash_swap.first_asset_pool(),
+            exchange.first_asset_pool() + asset_in_amount - treasury_fee
+        );
+
+        assert_eq!(
+            exchange_after_flash_swap.second_asset_pool(),
+            exchange.second_asset_pool() - asset_out_amount
+        );
+    })
+}
+
+#[test]
+fn flash_swap_reentrancy() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 10_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap borrowed parachain asset back through the flash swapped exchange
+        let flash_swap_result = emulate_flash_swap_with_path(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            500,
+            Asset::MainNetworkCurrency,
+            1_000,
+            vec![Asset::MainNetworkCurrency],
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            flash_swap_result,
+            pallet_subdex::Error::<Test>::FlashSwapReentrancy,
+            number_of_events_before_call,
+        );
+
+        // Ensure flash swap and inner swap rolled back
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        assert_eq!(Balances::free_balance(SecondAccountId::get()), 10_000);
+
+        assert_eq!(
+            exchange,
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            )
+        );
+    })
+}
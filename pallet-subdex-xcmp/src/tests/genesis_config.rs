@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn genesis_config_exchanges_and_asset_balances() {
+    let subdex_genesis_config = pallet_subdex::GenesisConfig {
+        asset_balances: vec![(SecondAccountId::get(), 1, 5_000)],
+        exchanges: vec![(
+            // reversed assets order, should be adjusted
+            Asset::ParachainAsset(1),
+            6_0000,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            FirstAccountId::get(),
+        )],
+        ..default_pallet_subdex_genesis_config()
+    };
+
+    ExtBuilder::build_with_subdex_genesis_config(subdex_genesis_config).execute_with(|| {
+        // Ensure parachain asset balances pre-seeded
+        assert_eq!(asset_balances(SecondAccountId::get(), 1), 5_000);
+
+        // Ensure exchange built the same way, as if it was initialized
+        let (genesis_exchange, initial_shares) = Exchange::<Test>::initialize_new(
+            10_0000,
+            6_0000,
+            FirstAccountId::get(),
+            PoolCurve::ConstantProduct,
+        )
+        .unwrap();
+
+        assert_eq!(
+            genesis_exchange,
+            dex_exchanges(Asset::MainNetworkCurrency, Asset::ParachainAsset(1))
+        );
+
+        // Ensure pools and positions indexed
+        assert_eq!(SubDex::pool_count(), 1);
+
+        assert_eq!(
+            SubDex::pools_of(Asset::ParachainAsset(1)),
+            vec![(Asset::MainNetworkCurrency, Asset::ParachainAsset(1))]
+        );
+
+        assert_eq!(
+            SubDex::positions_of(&FirstAccountId::get())[0].2,
+            initial_shares
+        );
+    })
+}
+
+#[test]
+#[should_panic(expected = "Invalid genesis exchange")]
+fn genesis_config_invalid_exchange() {
+    let subdex_genesis_config = pallet_subdex::GenesisConfig {
+        exchanges: vec![(
+            Asset::MainNetworkCurrency,
+            10_0000,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            FirstAccountId::get(),
+        )],
+        ..default_pallet_subdex_genesis_config()
+    };
+
+    ExtBuilder::build_with_subdex_genesis_config(subdex_genesis_config);
+}
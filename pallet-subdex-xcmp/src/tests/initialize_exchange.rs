@@ -51,6 +51,7 @@ fn initialize_exchange() {
             main_network_currency_transfer_amount,
             para_asset_transfer_amount,
             FirstAccountId::get(),
+            PoolCurve::ConstantProduct,
         )
         .unwrap();
 
@@ -70,8 +71,8 @@ fn initialize_exchange() {
         // Last event checked
         assert_event_success(
             exchange_initialized_event,
-            // additional event emitted when Currency slash() performed
-            number_of_events_before_call + 2,
+            // additional events emitted when Currency slash() performed and pool updated
+            number_of_events_before_call + 3,
         );
     })
 }
@@ -358,3 +359,45 @@ fn initialize_exchange_insufficient_parachain_asset_amount() {
         )
     })
 }
+
+#[test]
+fn initialize_exchange_pools_indexed() {
+    with_test_externalities(|| {
+        let first_asset_id = get_next_asset_id();
+
+        initialize_simple_exchange(FirstAccountId::get(), 10_000, Some(5), 6_000);
+
+        let second_asset_id = get_next_asset_id();
+
+        initialize_simple_exchange(FirstAccountId::get(), 10_000, Some(6), 6_000);
+
+        // Ensure both exchanges indexed by main network currency
+        let mut main_network_currency_pools = SubDex::pools_of(Asset::MainNetworkCurrency);
+        main_network_currency_pools.sort();
+
+        assert_eq!(
+            main_network_currency_pools,
+            vec![
+                (
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(first_asset_id)
+                ),
+                (
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(second_asset_id)
+                ),
+            ]
+        );
+
+        // Ensure exchange indexed by parachain asset, using adjusted assets order
+        assert_eq!(
+            SubDex::pools_of(Asset::ParachainAsset(first_asset_id)),
+            vec![(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(first_asset_id)
+            )]
+        );
+
+        assert_eq!(SubDex::pool_count(), 2);
+    })
+}
@@ -72,6 +72,7 @@ fn invest_liquidity() {
             main_network_currency_transfer_amount,
             para_asset_transfer_amount,
             FirstAccountId::get(),
+            PoolCurve::ConstantProduct,
         )
         .unwrap();
 
@@ -90,18 +91,117 @@ fn invest_liquidity() {
             )
         );
 
+        // Ensure post invest pool reserves emitted
+        let pool_updated_event = get_subdex_test_event(pallet_subdex::RawEvent::PoolUpdated(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            newly_created_exchange.first_asset_pool(),
+            newly_created_exchange.second_asset_pool(),
+            newly_created_exchange.total_shares,
+        ));
+
+        assert!(System::events()
+            .iter()
+            .any(|event_record| event_record.event == pool_updated_event));
+
         let exchange_invested_event = get_subdex_test_event(pallet_subdex::RawEvent::Invested(
             FirstAccountId::get(),
             Asset::MainNetworkCurrency,
             Asset::ParachainAsset(dex_para_asset_id),
             shares_to_be_own,
+            first_asset_cost,
+            second_asset_cost,
         ));
 
         // Last event checked
         assert_event_success(
             exchange_invested_event,
-            // additional event emitted when Currency slash() performed
-            number_of_events_before_call + 2,
+            // additional events emitted when Currency slash() performed and pool updated
+            number_of_events_before_call + 3,
+        );
+    })
+}
+
+#[test]
+fn invest_liquidity_large_pools() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+        // Pools exceed 1e18, so that pools and shares products exceed u128 range.
+
+        let main_network_currency_transfer_amount = 1_000_000_000_000_000_000_000;
+
+        let para_asset_transfer_amount = 600_000_000_000_000_000_000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Invest a tenth of total shares
+        let shares_to_be_own = exchange.total_shares / 10;
+
+        // Calculate an amount of both assets, needed to be invested, to own an exact amount of shares.
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_costs(shares_to_be_own).unwrap();
+
+        // Ensure costs are proportional to pools (rounded up)
+        assert!(first_asset_cost <= main_network_currency_transfer_amount / 10 + 1);
+        assert!(first_asset_cost > main_network_currency_transfer_amount / 10 * 99 / 100);
+        assert!(second_asset_cost <= para_asset_transfer_amount / 10 + 1);
+        assert!(second_asset_cost > para_asset_transfer_amount / 10 * 99 / 100);
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), first_asset_cost);
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            second_asset_cost,
+            para_asset_id,
+        );
+
+        assert_ok!(emulate_invest_liquidity(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            shares_to_be_own
+        ));
+
+        // Ensure both main network and parachain asset balances were successfully invested
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        assert_eq!(Balances::free_balance(SecondAccountId::get()), 0);
+
+        let exchange_after_invest = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_invest.shares(&SecondAccountId::get()),
+            shares_to_be_own
+        );
+        assert_eq!(
+            exchange_after_invest.first_asset_pool(),
+            main_network_currency_transfer_amount + first_asset_cost
+        );
+        assert_eq!(
+            exchange_after_invest.second_asset_pool(),
+            para_asset_transfer_amount + second_asset_cost
         );
     })
 }
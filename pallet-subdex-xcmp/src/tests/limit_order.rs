@@ -0,0 +1,486 @@
+use super::*;
+
+// Create order to swap given amount of parachain asset to main network currency at or above min price
+fn place_limit_order(
+    dex_para_asset_id: AssetId,
+    asset_in_amount: Balance,
+    min_price: U256,
+    expiry: u64,
+) {
+    assert_ok!(emulate_create_limit_order(
+        SecondAccountId::get(),
+        Asset::ParachainAsset(dex_para_asset_id),
+        asset_in_amount,
+        Asset::MainNetworkCurrency,
+        min_price,
+        expiry
+    ));
+}
+
+#[test]
+fn create_limit_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        let expiry = System::block_number() + 10;
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        place_limit_order(dex_para_asset_id, 1_000, price(1_500), expiry);
+
+        // Runtime tested state after call
+
+        // Ensure order amount and order deposit were reserved
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            9_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            1_000
+        );
+
+        assert_eq!(
+            Balances::reserved_balance(SecondAccountId::get()),
+            OrderDeposit::get()
+        );
+
+        let order = SubDex::limit_orders(0).unwrap();
+
+        assert_eq!(order.owner, SecondAccountId::get());
+        assert_eq!(order.amount_left, 1_000);
+        assert_eq!(order.next_check, System::block_number() + 1);
+        assert_eq!(order.deposit, OrderDeposit::get());
+
+        assert_eq!(SubDex::next_limit_order_id(), 1);
+
+        // Ensure order was pushed to the scheduled orders queue
+        assert_eq!(
+            SubDex::order_queue(System::block_number() + 1, 0),
+            Some(ScheduledOrder::Limit(0))
+        );
+
+        let limit_order_created_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::LimitOrderCreated(
+                0,
+                SecondAccountId::get(),
+                Asset::ParachainAsset(dex_para_asset_id),
+                Asset::MainNetworkCurrency,
+                1_000,
+                price(1_500),
+                expiry,
+            ));
+
+        // Last event checked (order deposit reserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            limit_order_created_event
+        );
+    })
+}
+
+#[test]
+fn limit_order_filled() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Spot price is 10_0000 / 6_0000, so order can be filled completely
+        place_limit_order(
+            dex_para_asset_id,
+            1_000,
+            price(1_500),
+            System::block_number() + 10,
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_second_to_first_asset_swap(1_000, None, None)
+            .unwrap();
+
+        let free_balance_before_block = Balances::free_balance(SecondAccountId::get());
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure swap output and order deposit were credited to the owner
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            free_balance_before_block + swap_delta.amount + OrderDeposit::get()
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        // Ensure order was completed
+        assert!(SubDex::limit_orders(0).is_none());
+
+        let limit_order_completed_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::LimitOrderCompleted(0));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            limit_order_completed_event
+        );
+    })
+}
+
+#[test]
+fn limit_order_partially_filled() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Order can only be filled partially, as average swap price decreases with swap amount
+        place_limit_order(
+            dex_para_asset_id,
+            10_000,
+            price(1_600),
+            System::block_number() + 10,
+        );
+
+        let free_balance_before_block = Balances::free_balance(SecondAccountId::get());
+
+        run_to_block(System::block_number() + 1);
+
+        let order = SubDex::limit_orders(0).unwrap();
+
+        let filled_amount = 10_000 - order.amount_left;
+
+        assert!(filled_amount > 0 && order.amount_left > 0);
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            order.amount_left
+        );
+
+        // Ensure order was filled at or above min price
+        let asset_out_amount =
+            Balances::free_balance(SecondAccountId::get()) - free_balance_before_block;
+
+        assert!(
+            U256::from(asset_out_amount) * price(1_000) >= U256::from(filled_amount) * price(1_600)
+        );
+
+        let limit_order_filled_event = get_subdex_test_event(
+            pallet_subdex::RawEvent::LimitOrderFilled(0, filled_amount, asset_out_amount),
+        );
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            limit_order_filled_event
+        );
+
+        // Ensure order remainder is checked again at the next block
+        assert_eq!(order.next_check, System::block_number() + 1);
+
+        assert_eq!(
+            SubDex::order_queue(order.next_check, 0),
+            Some(ScheduledOrder::Limit(0))
+        );
+
+        // Events number before tested block
+        let number_of_events_before_block = System::events().len();
+
+        run_to_block(System::block_number() + 1);
+
+        // Price moved below the limit, order can not be filled anymore
+        assert_eq!(
+            SubDex::limit_orders(0).unwrap().amount_left,
+            order.amount_left
+        );
+
+        assert_eq!(System::events().len(), number_of_events_before_block);
+    })
+}
+
+#[test]
+fn limit_order_price_not_reached() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_limit_order(
+            dex_para_asset_id,
+            1_000,
+            price(2_000),
+            System::block_number() + 10,
+        );
+
+        // Events number before tested block
+        let number_of_events_before_block = System::events().len();
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure order was not filled and is checked again at the next block
+        let order = SubDex::limit_orders(0).unwrap();
+
+        assert_eq!(order.amount_left, 1_000);
+        assert_eq!(order.next_check, System::block_number() + 1);
+
+        assert_eq!(System::events().len(), number_of_events_before_block);
+    })
+}
+
+#[test]
+fn limit_order_expired() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        let expiry = System::block_number() + 3;
+
+        place_limit_order(dex_para_asset_id, 1_000, price(2_000), expiry);
+
+        run_to_block(expiry);
+
+        // Ensure order was closed and its amount together with order deposit refunded to the owner
+        assert!(SubDex::limit_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            10_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        assert_eq!(SubDex::queued_orders_count(), 0);
+
+        let limit_order_expired_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::LimitOrderExpired(0, 1_000));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            limit_order_expired_event
+        );
+    })
+}
+
+#[test]
+fn limit_orders_checks_cap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        let orders_count = MaxScheduledOrdersPerBlock::get() as LimitOrderId + 1;
+
+        // Create more orders, than allowed to be processed per block
+        for _ in 0..orders_count {
+            place_limit_order(
+                dex_para_asset_id,
+                1_000,
+                price(2_000),
+                System::block_number() + 10,
+            );
+        }
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure only capped number of orders was checked, the rest was left in the queue
+        for order_id in 0..orders_count - 1 {
+            assert_eq!(
+                SubDex::limit_orders(order_id).unwrap().next_check,
+                System::block_number() + 1
+            );
+        }
+
+        assert_eq!(
+            SubDex::limit_orders(orders_count - 1).unwrap().next_check,
+            System::block_number()
+        );
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure left order was checked first and pushed to the end of the queue, while the last checked order still waits in the queue
+        assert_eq!(
+            SubDex::order_queue(System::block_number() + 1, 0),
+            Some(ScheduledOrder::Limit(orders_count - 1))
+        );
+
+        assert_eq!(
+            SubDex::order_queue(System::block_number(), 1),
+            Some(ScheduledOrder::Limit(orders_count - 2))
+        );
+
+        assert_eq!(SubDex::queued_orders_count(), orders_count as u32);
+    })
+}
+
+#[test]
+fn cancel_limit_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_limit_order(
+            dex_para_asset_id,
+            1_000,
+            price(2_000),
+            System::block_number() + 10,
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_cancel_limit_order(SecondAccountId::get(), 0));
+
+        // Runtime tested state after call
+
+        // Ensure order amount and order deposit were refunded to the owner
+        assert!(SubDex::limit_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            10_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        let limit_order_cancelled_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::LimitOrderCancelled(0, 1_000));
+
+        // Last event checked (order deposit unreserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            limit_order_cancelled_event
+        );
+
+        let number_of_events_after_call = System::events().len();
+
+        // Ensure cancelled order is not checked anymore and its queue entry is skipped
+        run_to_block(System::block_number() + 1);
+
+        assert_eq!(System::events().len(), number_of_events_after_call);
+
+        assert_eq!(SubDex::queued_orders_count(), 0);
+    })
+}
+
+#[test]
+fn cancel_limit_order_not_owner() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        place_limit_order(
+            dex_para_asset_id,
+            1_000,
+            price(2_000),
+            System::block_number() + 10,
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to cancel order, created by another account
+        let cancel_limit_order_result = emulate_cancel_limit_order(FirstAccountId::get(), 0);
+
+        // Failure checked
+        assert_subdex_failure(
+            cancel_limit_order_result,
+            pallet_subdex::Error::<Test>::NotLimitOrderOwner,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_limit_order_invalid_price() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to create order with zero min price
+        let create_limit_order_result = emulate_create_limit_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            U256::zero(),
+            System::block_number() + 10,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_limit_order_result,
+            pallet_subdex::Error::<Test>::InvalidLimitPrice,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_limit_order_invalid_expiry() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to create order, which expires before it can be checked
+        let create_limit_order_result = emulate_create_limit_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            price(1_500),
+            System::block_number() + 1,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_limit_order_result,
+            pallet_subdex::Error::<Test>::InvalidLimitOrderExpiry,
+            number_of_events_before_call,
+        );
+
+        // Make an attempt to create order, which stays open longer, than max order lifetime
+        let create_limit_order_result = emulate_create_limit_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            price(1_500),
+            System::block_number() + MaxOrderLifetime::get() + 1,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_limit_order_result,
+            pallet_subdex::Error::<Test>::InvalidLimitOrderExpiry,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,144 @@
+use super::*;
+
+// Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize liquidity bootstrapping exchange
+fn setup_bootstrapping_exchange(weight_schedule: WeightSchedule<u64>) -> AssetId {
+    let asset_id = get_next_asset_id();
+
+    // Emulate xcmp message
+    emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 90_0000, Some(5));
+
+    // Emulate downward message
+    emulate_downward_message(FirstAccountId::get(), 10_0000);
+
+    // Initialize new liquidity bootstrapping exchange
+    assert_ok!(emulate_initialize_bootstrapping_exchange(
+        FirstAccountId::get(),
+        // previosuly mapped parachain asset representation
+        Asset::ParachainAsset(asset_id),
+        90_0000,
+        Asset::MainNetworkCurrency,
+        10_0000,
+        weight_schedule
+    ));
+
+    asset_id
+}
+
+#[test]
+fn liquidity_bootstrapping_pool_weights_shift() {
+    with_test_externalities(|| {
+        // Parachain asset weight decreases from 90 to 50 during blocks 1..101
+        let dex_para_asset_id =
+            setup_bootstrapping_exchange(WeightSchedule::new((90, 10), (50, 50), 1, 101));
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure weights schedule was adjusted together with assets order
+        assert_eq!(
+            exchange.weight_schedule(),
+            Some(WeightSchedule::new((10, 90), (50, 50), 1, 101))
+        );
+
+        assert_eq!(exchange.curve(), PoolCurve::Weighted(10, 90));
+
+        let (swap_delta_at_start, _, _) = exchange
+            .calculate_first_to_second_asset_swap(1_000, None, None)
+            .unwrap();
+
+        run_to_block(51);
+
+        // Ensure weights interpolated linearly
+        assert_eq!(exchange.curve(), PoolCurve::Weighted(30, 70));
+
+        // Ensure parachain asset becomes cheaper over time
+        let (swap_delta_at_middle, _, _) = exchange
+            .calculate_first_to_second_asset_swap(1_000, None, None)
+            .unwrap();
+
+        assert!(swap_delta_at_middle.amount > swap_delta_at_start.amount);
+    })
+}
+
+#[test]
+fn liquidity_bootstrapping_pool_converted_to_regular_exchange() {
+    with_test_externalities(|| {
+        let dex_para_asset_id =
+            setup_bootstrapping_exchange(WeightSchedule::new((90, 10), (50, 50), 1, 101));
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        run_to_block(101);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Swap main network currency to parachain asset
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure exchange converted into a regular constant product one, once schedule ended
+        let exchange_after_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(exchange_after_swap.weight_schedule(), None);
+
+        assert_eq!(exchange_after_swap.curve(), PoolCurve::ConstantProduct);
+    })
+}
+
+#[test]
+fn liquidity_bootstrapping_pool_invalid_weight_schedule() {
+    with_test_externalities(|| {
+        let asset_id = get_next_asset_id();
+
+        // Emulate xcmp message
+        emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 90_0000, Some(5));
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), 10_0000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to initialize liquidity bootstrapping exchange with already ended schedule
+        let initialize_bootstrapping_exchange_result = emulate_initialize_bootstrapping_exchange(
+            FirstAccountId::get(),
+            // previosuly mapped parachain asset representation
+            Asset::ParachainAsset(asset_id),
+            90_0000,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            WeightSchedule::new((90, 10), (50, 50), 0, 1),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            initialize_bootstrapping_exchange_result,
+            pallet_subdex::Error::<Test>::InvalidWeightSchedule,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,152 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+#[test]
+fn swap_exact_to_price_impact_too_high() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to double main network currency pool without any expectations set: 1 - (100_000 / 200_000)^2 = 75%
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_0000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::PriceImpactTooHigh,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn set_max_price_impact() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Relax max price impact for given exchange pool
+        assert_ok!(emulate_set_max_price_impact(
+            Origin::root(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            Asset::MainNetworkCurrency,
+            Some(Permill::from_percent(80))
+        ));
+
+        // Runtime tested state after call
+
+        assert_eq!(
+            SubDex::max_price_impact(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id)
+            ),
+            Permill::from_percent(80)
+        );
+
+        let max_price_impact_set_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::MaxPriceImpactSet(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+                Some(Permill::from_percent(80)),
+            ));
+
+        // Last event checked
+        assert_event_success(max_price_impact_set_event, number_of_events_before_call + 1);
+
+        // Ensure swap, exceeding global limit, allowed for given exchange pool
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_0000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get()
+        ));
+
+        // Reset override, so that global limit is used again
+        assert_ok!(emulate_set_max_price_impact(
+            Origin::root(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            None
+        ));
+
+        assert_eq!(
+            SubDex::max_price_impact(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id)
+            ),
+            MaxPriceImpact::get()
+        );
+    })
+}
+
+#[test]
+fn set_max_price_impact_below_global_limit() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        // Tighten max price impact for given exchange pool
+        assert_ok!(emulate_set_max_price_impact(
+            Origin::root(),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Some(Permill::from_percent(10))
+        ));
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap, not exceeding global limit: 1 - (100_000 / 110_000)^2 ~ 17%
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::PriceImpactTooHigh,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn set_max_price_impact_bad_origin() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        // Make an attempt to set max price impact, using regular account
+        let set_max_price_impact_result = emulate_set_max_price_impact(
+            Origin::signed(FirstAccountId::get()),
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+            Some(Permill::from_percent(80)),
+        );
+
+        // Failure checked
+        assert_err!(set_max_price_impact_result, DispatchError::BadOrigin);
+    })
+}
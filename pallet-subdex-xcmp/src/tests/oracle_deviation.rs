@@ -0,0 +1,219 @@
+use super::*;
+use sp_core::U256;
+use sp_runtime::DispatchError;
+
+// Complete twap period and perform small swap, so that time weighted average price is calculated
+fn complete_twap_period(dex_para_asset_id: AssetId) {
+    set_timestamp(TwapPeriod::get());
+
+    assert_ok!(emulate_swap_exact_to(
+        SecondAccountId::get(),
+        Asset::MainNetworkCurrency,
+        100,
+        Asset::ParachainAsset(dex_para_asset_id),
+        0,
+        SecondAccountId::get()
+    ));
+}
+
+#[test]
+fn set_max_oracle_deviation() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_set_max_oracle_deviation(
+            Origin::root(),
+            Some(Permill::from_percent(5))
+        ));
+
+        // Runtime tested state after call
+
+        assert_eq!(
+            SubDex::max_oracle_deviation(),
+            Some(Permill::from_percent(5))
+        );
+
+        let max_oracle_deviation_set_event = get_subdex_test_event(
+            pallet_subdex::RawEvent::MaxOracleDeviationSet(Some(Permill::from_percent(5))),
+        );
+
+        // Last event checked
+        assert_event_success(
+            max_oracle_deviation_set_event,
+            number_of_events_before_call + 1,
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_oracle_deviation_too_high() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        assert_ok!(emulate_set_max_oracle_deviation(
+            Origin::root(),
+            Some(Permill::from_percent(5))
+        ));
+
+        complete_twap_period(dex_para_asset_id);
+
+        // Ensure average price equals to initial pools ratio, held during the whole period
+        assert_eq!(
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id)
+            )
+            .price1_average,
+            Some(U256::from(10_0000u128 * 1_000_000_000_000_000_000) / U256::from(6_0000))
+        );
+
+        // Ensure swap, keeping price close to average, is allowed
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            1_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get()
+        ));
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to move price within the same block: (111_100 / 54_000) / (100_000 / 60_000) ~ 1.23
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::OracleDeviationTooHigh,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_oracle_deviation_current_twap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        assert_ok!(emulate_set_max_oracle_deviation(
+            Origin::root(),
+            Some(Permill::from_percent(5))
+        ));
+
+        // Complete twap period without any swap, so that stored average price is not updated
+        set_timestamp(TwapPeriod::get());
+
+        assert_eq!(
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id)
+            )
+            .price1_average,
+            None
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to move price far from current time weighted average price
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::OracleDeviationTooHigh,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_oracle_deviation_guard_disabled() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        complete_twap_period(dex_para_asset_id);
+
+        // Ensure swap, moving price far from average, is allowed, when guard is not set
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            10_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get()
+        ));
+    })
+}
+
+#[test]
+fn set_max_oracle_deviation_bad_origin() {
+    with_test_externalities(|| {
+        // Make an attempt to set max oracle deviation, using regular account
+        let set_max_oracle_deviation_result = emulate_set_max_oracle_deviation(
+            Origin::signed(FirstAccountId::get()),
+            Some(Permill::from_percent(5)),
+        );
+
+        // Failure checked
+        assert_err!(set_max_oracle_deviation_result, DispatchError::BadOrigin);
+    })
+}
+
+#[test]
+fn flash_swap_oracle_deviation_too_high() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 0);
+
+        assert_ok!(emulate_set_max_oracle_deviation(
+            Origin::root(),
+            Some(Permill::from_percent(5))
+        ));
+
+        complete_twap_period(dex_para_asset_id);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to borrow parachain asset, moving price within the same block: (~110_100 / 54_000) / (100_000 / 60_000) ~ 1.22
+        let flash_swap_result = emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            6_000,
+            Asset::MainNetworkCurrency,
+            Balance::max_value(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            flash_swap_result,
+            pallet_subdex::Error::<Test>::OracleDeviationTooHigh,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,146 @@
+use super::*;
+use sp_core::U256;
+
+// Simple deterministic pseudo random numbers generator (xorshift), used to build operations sequences
+struct PseudoRandom(u64);
+
+impl PseudoRandom {
+    // Get next pseudo random number in range [1, max]
+    fn next(&mut self, max: Balance) -> Balance {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 as Balance) % max + 1
+    }
+}
+
+// Ensure pool value per share (sqrt(first_asset_pool * second_asset_pool) / total_shares) has not decreased
+fn assert_value_per_share_not_decreased(before: &Exchange<Test>, after: &Exchange<Test>) {
+    let total_shares_before = U256::from(before.total_shares);
+    let total_shares_after = U256::from(after.total_shares);
+    assert!(
+        after.invariant() * total_shares_before * total_shares_before
+            >= before.invariant() * total_shares_after * total_shares_after
+    );
+}
+
+#[test]
+fn value_per_share_never_decreases() {
+    for seed in 1..=10 {
+        with_test_externalities(|| {
+            let dex_para_asset_id =
+                setup_funded_exchange_with_pools(1_000_000, 600_000, 100_000_000, 100_000_000);
+
+            let mut pseudo_random = PseudoRandom(seed);
+
+            for _ in 0..100 {
+                let exchange_before = dex_exchanges(
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                );
+
+                // Operations are allowed to fail (e.g. when account does not own enough shares),
+                // exchange state should remain consistent anyway
+                let _ = match pseudo_random.next(4) {
+                    1 => emulate_invest_liquidity(
+                        SecondAccountId::get(),
+                        Asset::MainNetworkCurrency,
+                        Asset::ParachainAsset(dex_para_asset_id),
+                        pseudo_random.next(1_000),
+                    ),
+                    2 => emulate_divest_liquidity(
+                        SecondAccountId::get(),
+                        Asset::MainNetworkCurrency,
+                        Asset::ParachainAsset(dex_para_asset_id),
+                        pseudo_random.next(1_000),
+                        0,
+                        0,
+                    ),
+                    3 => emulate_swap_exact_to(
+                        SecondAccountId::get(),
+                        Asset::MainNetworkCurrency,
+                        pseudo_random.next(10_000),
+                        Asset::ParachainAsset(dex_para_asset_id),
+                        0,
+                        SecondAccountId::get(),
+                    ),
+                    _ => emulate_swap_exact_to(
+                        SecondAccountId::get(),
+                        Asset::ParachainAsset(dex_para_asset_id),
+                        pseudo_random.next(10_000),
+                        Asset::MainNetworkCurrency,
+                        0,
+                        SecondAccountId::get(),
+                    ),
+                };
+
+                let exchange_after = dex_exchanges(
+                    Asset::MainNetworkCurrency,
+                    Asset::ParachainAsset(dex_para_asset_id),
+                );
+
+                assert_value_per_share_not_decreased(&exchange_before, &exchange_after);
+            }
+        })
+    }
+}
+
+#[test]
+fn tiny_invests_do_not_drain_value() {
+    with_test_externalities(|| {
+        let dex_para_asset_id =
+            setup_funded_exchange_with_pools(1_000_000, 600_000, 100_000_000, 100_000_000);
+
+        for _ in 0..100 {
+            let exchange_before = dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            );
+
+            // Even a single share should cost at least one unit of each asset
+            let (first_asset_cost, second_asset_cost) = exchange_before.calculate_costs(1).unwrap();
+            assert!(first_asset_cost >= 1 && second_asset_cost >= 1);
+
+            assert_ok!(emulate_invest_liquidity(
+                SecondAccountId::get(),
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+                1
+            ));
+
+            let exchange_after = dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            );
+
+            assert_value_per_share_not_decreased(&exchange_before, &exchange_after);
+        }
+    })
+}
+
+#[test]
+fn divest_amounts_never_exceed_invest_costs() {
+    with_test_externalities(|| {
+        let dex_para_asset_id =
+            setup_funded_exchange_with_pools(1_000_000, 600_000, 100_000_000, 100_000_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let mut pseudo_random = PseudoRandom(42);
+
+        for _ in 0..100 {
+            let shares = pseudo_random.next(exchange.total_shares);
+
+            let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares).unwrap();
+
+            let (first_asset_amount, second_asset_amount) =
+                exchange.calculate_divest_amounts(shares).unwrap();
+
+            assert!(first_asset_amount <= first_asset_cost);
+            assert!(second_asset_amount <= second_asset_cost);
+        }
+    })
+}
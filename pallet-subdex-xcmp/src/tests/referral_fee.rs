@@ -0,0 +1,269 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+#[test]
+fn set_referral_share() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_set_referral_share(
+            Origin::root(),
+            Permill::from_percent(20)
+        ));
+
+        // Runtime tested state after call
+
+        assert_eq!(SubDex::referral_share(), Permill::from_percent(20));
+
+        let referral_share_set_event = get_subdex_test_event(
+            pallet_subdex::RawEvent::ReferralShareSet(Permill::from_percent(20)),
+        );
+
+        // Last event checked
+        assert_event_success(referral_share_set_event, number_of_events_before_call + 1);
+    })
+}
+
+#[test]
+fn swap_exact_to_referral_fee() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let para_asset_id = Some(5);
+
+        let asset_in_amount = 10_000;
+
+        assert_ok!(emulate_set_referral_share(
+            Origin::root(),
+            Permill::from_percent(20)
+        ));
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            para_asset_id,
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta, treasury fee and referral fee
+        let (swap_delta, treasury_fee_data, referral_fee_data) = exchange
+            .calculate_second_to_first_asset_swap(
+                asset_in_amount,
+                None,
+                Some(FirstAccountId::get()),
+            )
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        let (referral_fee, _) = referral_fee_data.unwrap();
+
+        // Joint fee 30 is split into treasury fee 30 / 4 and referral fee 30 * 20%, the rest is left in the pool
+        assert_eq!(
+            exchange.calculate_joint_fee(asset_in_amount, None).unwrap(),
+            30
+        );
+        assert_eq!(treasury_fee, 7);
+        assert_eq!(referral_fee, 6);
+
+        // Ensure treasury and referral fees are paid out of asset in amount and never enter the pool
+        assert_eq!(
+            swap_delta.second_asset_pool,
+            6_0000 + asset_in_amount - treasury_fee - referral_fee
+        );
+
+        // Ensure referral share does not improve swapper rate
+        let (swap_delta_without_referrer, _, _) = exchange
+            .calculate_second_to_first_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        assert_eq!(swap_delta.amount, swap_delta_without_referrer.amount);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_swap_exact_to_with_referrer(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            SecondAccountId::get(),
+            FirstAccountId::get()
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure referral fee was minted to referrer in asset in
+        assert_eq!(
+            asset_balances(FirstAccountId::get(), dex_para_asset_id),
+            referral_fee
+        );
+
+        assert_eq!(
+            asset_balances(TreasuryAccountId::get(), dex_para_asset_id),
+            treasury_fee
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            swap_delta.amount
+        );
+
+        let exchanged_event = get_subdex_test_event(pallet_subdex::RawEvent::Exchanged(
+            SecondAccountId::get(),
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            30,
+            Some(treasury_fee),
+            Some((FirstAccountId::get(), referral_fee)),
+        ));
+
+        // Last event checked
+        assert_event_success(
+            exchanged_event,
+            // additional events emitted when Currency deposit_creating() method performed and pool updated
+            number_of_events_before_call + 4,
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_referral_fee_total_supply_unchanged() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        let para_asset_id = Some(5);
+
+        let asset_in_amount = 10_000;
+
+        assert_ok!(emulate_set_referral_share(
+            Origin::root(),
+            Permill::from_percent(20)
+        ));
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            para_asset_id,
+        );
+
+        // Asset in total supply: sender, referrer and treasury balances together with exchange pool
+        let asset_in_total_supply = || {
+            let exchange = dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            );
+
+            asset_balances(SecondAccountId::get(), dex_para_asset_id)
+                + asset_balances(FirstAccountId::get(), dex_para_asset_id)
+                + asset_balances(TreasuryAccountId::get(), dex_para_asset_id)
+                + exchange.second_asset_pool()
+        };
+
+        // Runtime tested state before call
+
+        let asset_in_total_supply_before_call = asset_in_total_supply();
+
+        assert_ok!(emulate_swap_exact_to_with_referrer(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            0,
+            SecondAccountId::get(),
+            FirstAccountId::get()
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure referred swap neither mints nor burns asset in
+        assert!(asset_balances(FirstAccountId::get(), dex_para_asset_id) > 0);
+
+        assert_eq!(asset_in_total_supply(), asset_in_total_supply_before_call);
+    })
+}
+
+#[test]
+fn swap_exact_to_invalid_referrer() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_exchange();
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to refer own swap
+        let swap_exact_to_result = emulate_swap_exact_to_with_referrer(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            1_000,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::InvalidReferrer,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn set_referral_share_invalid() {
+    with_test_externalities(|| {
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to set referral share, exceeding joint fee together with treasury share (1/4)
+        let set_referral_share_result =
+            emulate_set_referral_share(Origin::root(), Permill::from_percent(80));
+
+        // Failure checked
+        assert_subdex_failure(
+            set_referral_share_result,
+            pallet_subdex::Error::<Test>::InvalidReferralShare,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn set_referral_share_bad_origin() {
+    with_test_externalities(|| {
+        // Make an attempt to set referral share, using regular account
+        let set_referral_share_result = emulate_set_referral_share(
+            Origin::signed(FirstAccountId::get()),
+            Permill::from_percent(20),
+        );
+
+        // Failure checked
+        assert_err!(set_referral_share_result, DispatchError::BadOrigin);
+    })
+}
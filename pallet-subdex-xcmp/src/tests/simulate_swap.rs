@@ -0,0 +1,117 @@
+use super::*;
+
+#[test]
+fn simulate_swap() {
+    with_test_externalities(|| {
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(FirstAccountId::get(), 10_0000, Some(5), 6_0000);
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let asset_in_amount = 10_000;
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            Some(5),
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let swap_simulation = SubDex::simulate_swap(
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            Some(SecondAccountId::get()),
+        )
+        .unwrap();
+
+        // Ensure simulation did not change exchange state
+        assert_eq!(
+            exchange,
+            dex_exchanges(
+                Asset::MainNetworkCurrency,
+                Asset::ParachainAsset(dex_para_asset_id),
+            )
+        );
+
+        // Ensure fee breakdown sums up to joint fee: 10_000 * 3 / 1000, treasury takes 1/4 of it (rounded down)
+        assert_eq!(swap_simulation.treasury_fee, Some(7));
+        assert_eq!(swap_simulation.lp_fee, 23);
+
+        // Ensure price impact matches pools ratio change: 1 - (60_000 / 70_000)^2 ~ 26.5%
+        assert!(
+            swap_simulation.price_impact > Permill::from_percent(25)
+                && swap_simulation.price_impact < Permill::from_percent(28)
+        );
+
+        // Ensure effective price equals asset out amount per asset in amount (fixed point)
+        assert_eq!(
+            swap_simulation.effective_price,
+            U256::from(swap_simulation.swap_delta.amount)
+                * U256::from(1_000_000_000_000_000_000u128)
+                / U256::from(asset_in_amount)
+        );
+
+        // Ensure effective price is below spot price 10_0000 / 6_0000 because of fee and slippage
+        assert!(
+            swap_simulation.effective_price
+                < U256::from(10_0000u128) * U256::from(1_000_000_000_000_000_000u128)
+                    / U256::from(6_0000u128)
+        );
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_simulation.swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure simulation matches performed swap
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            swap_simulation.swap_delta.amount
+        );
+
+        let exchange_after_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_swap.first_asset_pool(),
+            swap_simulation.swap_delta.first_asset_pool
+        );
+
+        assert_eq!(
+            exchange_after_swap.second_asset_pool(),
+            swap_simulation.swap_delta.second_asset_pool
+        );
+    })
+}
+
+#[test]
+fn simulate_swap_exchange_does_not_exist() {
+    with_test_externalities(|| {
+        // Make an attempt to simulate swap for exchange, which does not exist
+        let swap_simulation_result = SubDex::simulate_swap(
+            Asset::ParachainAsset(1),
+            10_000,
+            Asset::MainNetworkCurrency,
+            None,
+        );
+
+        assert_eq!(
+            swap_simulation_result,
+            Err(pallet_subdex::Error::<Test>::ExchangeNotExists.into())
+        );
+    })
+}
@@ -0,0 +1,166 @@
+use super::*;
+
+// Initialize exchange with a given curve and equal pools
+fn setup_stable_swap_exchange(curve: PoolCurve) -> AssetId {
+    setup_exchange_with_curve(10_0000, 10_0000, curve)
+}
+
+#[test]
+fn stable_swap_initialize_exchange() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_stable_swap_exchange(PoolCurve::StableSwap(100));
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure exchange uses StableSwap curve and initial shares equal to invariant of balanced pools
+        assert_eq!(exchange.curve(), PoolCurve::StableSwap(100));
+
+        assert_eq!(exchange.total_shares, 20_0000);
+    })
+}
+
+#[test]
+fn stable_swap_swap_exact_to() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_stable_swap_exchange(PoolCurve::StableSwap(100));
+
+        let asset_in_amount = 10_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Ensure StableSwap curve provides better price, than constant product one for balanced pools
+        let (constant_product_exchange, _) = Exchange::<Test>::initialize_new(
+            exchange.first_asset_pool(),
+            exchange.second_asset_pool(),
+            FirstAccountId::get(),
+            PoolCurve::ConstantProduct,
+        )
+        .unwrap();
+
+        let (constant_product_swap_delta, _, _) = constant_product_exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        assert!(swap_delta.amount > constant_product_swap_delta.amount);
+
+        // Ensure fee is still charged
+        assert!(swap_delta.amount < asset_in_amount);
+
+        // Swap main network currency to parachain asset
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure swap output credited
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            swap_delta.amount
+        );
+
+        let exchange_after_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_swap.second_asset_pool(),
+            exchange.second_asset_pool() - swap_delta.amount
+        );
+    })
+}
+
+#[test]
+fn stable_swap_flash_swap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_stable_swap_exchange(PoolCurve::StableSwap(100));
+
+        let asset_out_amount = 10_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 20_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate main network currency amount, needed to repay flash swap
+        let asset_in_amount = exchange
+            .calculate_first_asset_flash_swap_repayment(asset_out_amount, None)
+            .unwrap();
+
+        // Borrow parachain asset and repay it with main network currency
+        assert_ok!(emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_out_amount,
+            Asset::MainNetworkCurrency,
+            asset_in_amount
+        ));
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            asset_out_amount
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            20_000 - asset_in_amount
+        );
+    })
+}
+
+#[test]
+fn stable_swap_invalid_amplification_coefficient() {
+    with_test_externalities(|| {
+        let asset_id = get_next_asset_id();
+
+        // Emulate xcmp message
+        emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 10_0000, Some(5));
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), 10_0000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to initialize StableSwap exchange with zero amplification coefficient
+        let initialize_new_exchange_result = initialize_new_exchange_with_curve(
+            FirstAccountId::get(),
+            // previosuly mapped parachain asset representation
+            Asset::ParachainAsset(asset_id),
+            10_0000,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            PoolCurve::StableSwap(0),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            initialize_new_exchange_result,
+            pallet_subdex::Error::<Test>::InvalidAmplificationCoefficient,
+            number_of_events_before_call,
+        );
+    })
+}
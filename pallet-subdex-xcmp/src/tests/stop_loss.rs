@@ -0,0 +1,488 @@
+use super::*;
+
+// Create order to swap 1_000 of parachain asset to main network currency, once its average price falls to trigger price
+fn place_stop_loss_order(
+    dex_para_asset_id: AssetId,
+    trigger_price: U256,
+    min_asset_out_amount: Balance,
+    expiry: u64,
+) {
+    assert_ok!(emulate_create_stop_loss_order(
+        SecondAccountId::get(),
+        Asset::ParachainAsset(dex_para_asset_id),
+        1_000,
+        Asset::MainNetworkCurrency,
+        trigger_price,
+        min_asset_out_amount,
+        expiry
+    ));
+}
+
+// Sell parachain asset, so that its price drops from 10_0000 / 6_0000 to ~ 8_7000 / 6_9000, and complete twap period
+fn drop_parachain_asset_price(dex_para_asset_id: AssetId) {
+    assert_ok!(emulate_swap_exact_to(
+        SecondAccountId::get(),
+        Asset::ParachainAsset(dex_para_asset_id),
+        9_000,
+        Asset::MainNetworkCurrency,
+        0,
+        SecondAccountId::get()
+    ));
+
+    set_timestamp(TwapPeriod::get());
+}
+
+#[test]
+fn create_stop_loss_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        let expiry = System::block_number() + 10;
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        place_stop_loss_order(dex_para_asset_id, price(1_500), 0, expiry);
+
+        // Runtime tested state after call
+
+        // Ensure order amount and order deposit were reserved
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            9_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            1_000
+        );
+
+        assert_eq!(
+            Balances::reserved_balance(SecondAccountId::get()),
+            OrderDeposit::get()
+        );
+
+        let order = SubDex::stop_loss_orders(0).unwrap();
+
+        assert_eq!(order.owner, SecondAccountId::get());
+        assert_eq!(order.trigger_price, price(1_500));
+        assert_eq!(order.next_check, System::block_number() + 1);
+        assert_eq!(order.deposit, OrderDeposit::get());
+
+        assert_eq!(SubDex::next_stop_loss_order_id(), 1);
+
+        // Ensure order was pushed to the scheduled orders queue
+        assert_eq!(
+            SubDex::order_queue(System::block_number() + 1, 0),
+            Some(ScheduledOrder::StopLoss(0))
+        );
+
+        let stop_loss_order_created_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::StopLossOrderCreated(
+                0,
+                SecondAccountId::get(),
+                Asset::ParachainAsset(dex_para_asset_id),
+                Asset::MainNetworkCurrency,
+                1_000,
+                price(1_500),
+                expiry,
+            ));
+
+        // Last event checked (order deposit reserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            stop_loss_order_created_event
+        );
+    })
+}
+
+#[test]
+fn stop_loss_order_triggered() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        place_stop_loss_order(
+            dex_para_asset_id,
+            price(1_500),
+            0,
+            System::block_number() + 10,
+        );
+
+        // Ensure order is not triggered, while average price is not available yet
+        run_to_block(System::block_number() + 1);
+
+        assert!(SubDex::stop_loss_orders(0).is_some());
+
+        drop_parachain_asset_price(dex_para_asset_id);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        let twap_price = exchange.calculate_twap_price(false).unwrap().unwrap();
+
+        assert!(twap_price < price(1_500));
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_second_to_first_asset_swap(1_000, None, None)
+            .unwrap();
+
+        let main_network_balance_before_block = Balances::free_balance(SecondAccountId::get());
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure swap output and order deposit were credited to the owner
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            main_network_balance_before_block + swap_delta.amount + OrderDeposit::get()
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        assert!(SubDex::stop_loss_orders(0).is_none());
+
+        let stop_loss_order_triggered_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::StopLossOrderTriggered(
+                0,
+                twap_price,
+                1_000,
+                swap_delta.amount,
+            ));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            stop_loss_order_triggered_event
+        );
+    })
+}
+
+#[test]
+fn stop_loss_order_failed() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        // Create order with min asset out amount, which can not be satisfied
+        place_stop_loss_order(
+            dex_para_asset_id,
+            price(1_500),
+            10_000,
+            System::block_number() + 10,
+        );
+
+        drop_parachain_asset_price(dex_para_asset_id);
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure order was closed and its amount together with order deposit refunded to the owner
+        assert!(SubDex::stop_loss_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            1_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        let stop_loss_order_failed_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::StopLossOrderFailed(0, 1_000));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            stop_loss_order_failed_event
+        );
+    })
+}
+
+#[test]
+fn stop_loss_order_not_triggered() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        place_stop_loss_order(
+            dex_para_asset_id,
+            price(1_000),
+            0,
+            System::block_number() + 10,
+        );
+
+        // Complete twap period without price change
+        set_timestamp(TwapPeriod::get());
+
+        // Events number before tested block
+        let number_of_events_before_block = System::events().len();
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure order was not triggered and is checked again at the next block
+        let order = SubDex::stop_loss_orders(0).unwrap();
+
+        assert_eq!(order.next_check, System::block_number() + 1);
+
+        assert_eq!(
+            SubDex::order_queue(order.next_check, 0),
+            Some(ScheduledOrder::StopLoss(0))
+        );
+
+        assert_eq!(System::events().len(), number_of_events_before_block);
+    })
+}
+
+#[test]
+fn stop_loss_order_expired() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        let expiry = System::block_number() + 2;
+
+        place_stop_loss_order(dex_para_asset_id, price(1_500), 0, expiry);
+
+        run_to_block(expiry);
+
+        // Ensure order was closed and its amount together with order deposit refunded to the owner
+        assert!(SubDex::stop_loss_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            10_000
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        assert_eq!(SubDex::queued_orders_count(), 0);
+
+        let stop_loss_order_expired_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::StopLossOrderExpired(0, 1_000));
+
+        // Last event checked
+        assert_eq!(
+            System::events().last().unwrap().event,
+            stop_loss_order_expired_event
+        );
+    })
+}
+
+#[test]
+fn stop_loss_orders_checks_cap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        let orders_count = MaxScheduledOrdersPerBlock::get() as StopLossOrderId + 1;
+
+        // Create more orders, than allowed to be processed per block
+        for _ in 0..orders_count {
+            place_stop_loss_order(
+                dex_para_asset_id,
+                price(1_500),
+                0,
+                System::block_number() + 10,
+            );
+        }
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure only capped number of orders was checked, the rest was left in the queue
+        for order_id in 0..orders_count - 1 {
+            assert_eq!(
+                SubDex::stop_loss_orders(order_id).unwrap().next_check,
+                System::block_number() + 1
+            );
+        }
+
+        assert_eq!(
+            SubDex::stop_loss_orders(orders_count - 1)
+                .unwrap()
+                .next_check,
+            System::block_number()
+        );
+
+        run_to_block(System::block_number() + 1);
+
+        // Ensure left order was checked first and pushed to the end of the queue, while the last checked order still waits in the queue
+        assert_eq!(
+            SubDex::order_queue(System::block_number() + 1, 0),
+            Some(ScheduledOrder::StopLoss(orders_count - 1))
+        );
+
+        assert_eq!(
+            SubDex::order_queue(System::block_number(), 1),
+            Some(ScheduledOrder::StopLoss(orders_count - 2))
+        );
+
+        assert_eq!(SubDex::queued_orders_count(), orders_count as u32);
+    })
+}
+
+#[test]
+fn cancel_stop_loss_order() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        place_stop_loss_order(
+            dex_para_asset_id,
+            price(1_500),
+            0,
+            System::block_number() + 10,
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        assert_ok!(emulate_cancel_stop_loss_order(SecondAccountId::get(), 0));
+
+        // Runtime tested state after call
+
+        // Ensure order amount and order deposit were refunded to the owner
+        assert!(SubDex::stop_loss_orders(0).is_none());
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            10_000
+        );
+
+        assert_eq!(
+            SubDex::reserved_asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            0
+        );
+
+        assert_eq!(Balances::reserved_balance(SecondAccountId::get()), 0);
+
+        let stop_loss_order_cancelled_event =
+            get_subdex_test_event(pallet_subdex::RawEvent::StopLossOrderCancelled(0, 1_000));
+
+        // Last event checked (order deposit unreserve may emit additional events)
+        assert!(System::events().len() > number_of_events_before_call);
+
+        assert_eq!(
+            System::events().last().unwrap().event,
+            stop_loss_order_cancelled_event
+        );
+    })
+}
+
+#[test]
+fn cancel_stop_loss_order_not_owner() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        place_stop_loss_order(
+            dex_para_asset_id,
+            price(1_500),
+            0,
+            System::block_number() + 10,
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to cancel order, created by another account
+        let cancel_stop_loss_order_result =
+            emulate_cancel_stop_loss_order(FirstAccountId::get(), 0);
+
+        // Failure checked
+        assert_subdex_failure(
+            cancel_stop_loss_order_result,
+            pallet_subdex::Error::<Test>::NotStopLossOrderOwner,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_stop_loss_order_invalid_trigger_price() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to create order with zero trigger price
+        let create_stop_loss_order_result = emulate_create_stop_loss_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            U256::zero(),
+            0,
+            System::block_number() + 10,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_stop_loss_order_result,
+            pallet_subdex::Error::<Test>::InvalidTriggerPrice,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn create_stop_loss_order_invalid_expiry() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_funded_exchange(10_0000, 10_000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to create order, which expires before it can be checked
+        let create_stop_loss_order_result = emulate_create_stop_loss_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            price(1_500),
+            0,
+            System::block_number() + 1,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_stop_loss_order_result,
+            pallet_subdex::Error::<Test>::InvalidStopLossOrderExpiry,
+            number_of_events_before_call,
+        );
+
+        // Make an attempt to create order, which stays open longer, than max order lifetime
+        let create_stop_loss_order_result = emulate_create_stop_loss_order(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            1_000,
+            Asset::MainNetworkCurrency,
+            price(1_500),
+            0,
+            System::block_number() + MaxOrderLifetime::get() + 1,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            create_stop_loss_order_result,
+            pallet_subdex::Error::<Test>::InvalidStopLossOrderExpiry,
+            number_of_events_before_call,
+        );
+    })
+}
@@ -0,0 +1,219 @@
+use super::*;
+
+#[test]
+fn swap_and_transfer_to_parachain_chain() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Swap main network currency to parachain asset and send it back to its parachain
+        assert_ok!(emulate_swap_and_transfer_to_parachain_chain(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            para_asset_id,
+            swap_delta.amount
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure asset in amount was slashed and swap output was transferred to another parachain
+        assert_eq!(Balances::free_balance(SecondAccountId::get()), 0);
+
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        let withdraw_asset_via_xcmp_event =
+            get_subdex_xcmp_test_event(RawEvent::WithdrawAssetViaXCMP(
+                FirstParaId::get(),
+                para_asset_id,
+                FirstAccountId::get(),
+                dex_para_asset_id,
+                swap_delta.amount,
+            ));
+
+        // Last event checked
+        assert_event_success(
+            withdraw_asset_via_xcmp_event,
+            // additional events emitted when Currency slash() and swap performed and pool updated
+            number_of_events_before_call + 4,
+        );
+    })
+}
+
+#[test]
+fn swap_and_transfer_to_parachain_chain_asset_id_does_not_exist() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap to parachain asset, which was never mapped
+        let swap_and_transfer_result = emulate_swap_and_transfer_to_parachain_chain(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            Some(6),
+            1,
+        );
+
+        // Failure checked
+        assert_subdex_xcmp_failure(
+            swap_and_transfer_result,
+            Error::<Test>::AssetIdDoesNotExist,
+            number_of_events_before_call,
+        );
+
+        // Ensure swap was not performed
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            asset_in_amount
+        );
+    })
+}
+
+#[test]
+fn swap_and_transfer_to_parachain_chain_swap_failed() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap, expecting more than exchange can provide
+        let swap_and_transfer_result = emulate_swap_and_transfer_to_parachain_chain(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_and_transfer_result,
+            pallet_subdex::Error::<Test>::SecondAssetAmountBelowExpectation,
+            number_of_events_before_call,
+        );
+
+        // Ensure swap was not performed
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            asset_in_amount
+        );
+    })
+}
+
+#[test]
+fn swap_and_transfer_to_parachain_chain_zero_balance() {
+    with_test_externalities(|| {
+        let para_asset_id = Some(5);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap zero amount
+        let swap_and_transfer_result = emulate_swap_and_transfer_to_parachain_chain(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            0,
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            para_asset_id,
+            1,
+        );
+
+        // Failure checked
+        assert_subdex_xcmp_failure(
+            swap_and_transfer_result,
+            Error::<Test>::AmountShouldBeGreaterThanZero,
+            number_of_events_before_call,
+        );
+    })
+}
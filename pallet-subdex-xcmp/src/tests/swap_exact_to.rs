@@ -0,0 +1,320 @@
+use super::*;
+
+#[test]
+fn swap_exact_to_receiver() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let asset_in_amount = 1_000;
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            para_asset_id,
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta and treasury fee
+        let (swap_delta, treasury_fee_data, _) = exchange
+            .calculate_second_to_first_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        let (treasury_fee, _) = treasury_fee_data.unwrap();
+
+        let joint_fee = exchange.calculate_joint_fee(asset_in_amount, None).unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Swap parachain asset to main network currency, sending proceeds to another account
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            FirstAccountId::get()
+        ));
+
+        // Runtime tested state after call
+
+        // Ensure asset in amount was slashed from sender
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        // Ensure asset out amount was minted to receiver, not to sender
+        assert_eq!(
+            Balances::free_balance(FirstAccountId::get()),
+            swap_delta.amount
+        );
+        assert_eq!(Balances::free_balance(SecondAccountId::get()), 0);
+
+        let exchanged_event = get_subdex_test_event(pallet_subdex::RawEvent::Exchanged(
+            SecondAccountId::get(),
+            FirstAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            joint_fee,
+            Some(treasury_fee),
+            None,
+        ));
+
+        // Last event checked
+        assert_event_success(
+            exchanged_event,
+            // additional events emitted when Currency deposit_creating() method performed and pool updated
+            number_of_events_before_call + 4,
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_large_pools() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+        // Pools exceed 1e18, so that intermediate pools product exceeds u128 range.
+
+        let main_network_currency_transfer_amount = 1_000_000_000_000_000_000_000;
+
+        let para_asset_transfer_amount = 600_000_000_000_000_000_000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let asset_in_amount = 10_000_000_000_000_000_000;
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            SecondAccountId::get(),
+            asset_in_amount,
+            para_asset_id,
+        );
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_second_to_first_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Ensure swap output follows spot price (10 / 6) less slippage and fee
+        assert!(swap_delta.amount < asset_in_amount * 10 / 6);
+        assert!(swap_delta.amount > asset_in_amount * 10 / 6 * 97 / 100);
+
+        // Ensure pools invariant was not decreased
+        assert!(
+            U256::from(swap_delta.first_asset_pool) * U256::from(swap_delta.second_asset_pool)
+                >= U256::from(exchange.first_asset_pool())
+                    * U256::from(exchange.second_asset_pool())
+        );
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_in_amount,
+            Asset::MainNetworkCurrency,
+            swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure asset in amount was slashed and asset out amount was minted
+        assert_eq!(asset_balances(SecondAccountId::get(), dex_para_asset_id), 0);
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            swap_delta.amount
+        );
+
+        let exchange_after_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_swap.first_asset_pool(),
+            swap_delta.first_asset_pool
+        );
+        assert_eq!(
+            exchange_after_swap.second_asset_pool(),
+            swap_delta.second_asset_pool
+        );
+    })
+}
+
+#[test]
+fn swap_exact_to_invalid_exchange() {
+    with_test_externalities(|| {
+        let transfer_amount = 10_000;
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), transfer_amount);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap, providing the same asset in and asset out
+        let swap_exact_to_result = emulate_swap_exact_to(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            transfer_amount,
+            Asset::MainNetworkCurrency,
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::InvalidExchange,
+            number_of_events_before_call,
+        )
+    })
+}
+
+#[test]
+fn swap_exact_to_exchange_does_not_exist() {
+    with_test_externalities(|| {
+        let transfer_amount = 10_000;
+
+        let para_asset_id = Some(5);
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), transfer_amount);
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            transfer_amount,
+            para_asset_id,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap using non existent exchange
+        let swap_exact_to_result = emulate_swap_exact_to(
+            FirstAccountId::get(),
+            Asset::MainNetworkCurrency,
+            transfer_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::ExchangeNotExists,
+            number_of_events_before_call,
+        )
+    })
+}
+
+#[test]
+fn swap_exact_to_second_asset_amount_below_expectation() {
+    with_test_externalities(|| {
+        // Transfer both main network currency and custom parachain assets to dex parachain.
+
+        let main_network_currency_transfer_amount = 10_0000;
+
+        let para_asset_transfer_amount = 6_0000;
+
+        let para_asset_id = Some(5);
+
+        // Receive provided amounts for both main network curency and parachain assets through xcmp and use them to initialize exchange
+        initialize_simple_exchange(
+            FirstAccountId::get(),
+            main_network_currency_transfer_amount,
+            para_asset_id,
+            para_asset_transfer_amount,
+        );
+
+        // previosuly mapped parachain asset representation
+        let dex_para_asset_id = get_next_asset_id() - 1;
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap, expecting more than exchange can provide
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount + 1,
+            FirstAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::SecondAssetAmountBelowExpectation,
+            number_of_events_before_call,
+        )
+    })
+}
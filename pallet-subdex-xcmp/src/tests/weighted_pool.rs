@@ -0,0 +1,266 @@
+use super::*;
+use sp_runtime::DispatchError;
+
+// Initialize 80/20 weighted exchange, providing liquidity mostly in parachain asset
+fn setup_weighted_exchange() -> AssetId {
+    setup_exchange_with_curve(40_0000, 10_0000, PoolCurve::Weighted(80, 20))
+}
+
+#[test]
+fn weighted_pool_initialize_exchange() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_weighted_exchange();
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure weights were adjusted together with assets order
+        assert_eq!(exchange.curve(), PoolCurve::Weighted(20, 80));
+
+        // Initial shares equal to weighted geometric mean of both pools: 400000^0.8 * 100000^0.2 ~ 303143
+        assert_eq!(exchange.total_shares, 303_143);
+    })
+}
+
+#[test]
+fn weighted_pool_swap_exact_to() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_weighted_exchange();
+
+        let asset_in_amount = 1_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate expected swap delta
+        let (swap_delta, _, _) = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .unwrap();
+
+        // Both assets have the same spot price, given weights and pools,
+        // so swap output is slightly below input because of fee and slippage
+        assert!(swap_delta.amount > 985 && swap_delta.amount < 997);
+
+        // Swap main network currency to parachain asset
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount,
+            SecondAccountId::get()
+        ));
+
+        // Ensure swap output credited
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            swap_delta.amount
+        );
+
+        let exchange_after_swap = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        assert_eq!(
+            exchange_after_swap.second_asset_pool(),
+            exchange.second_asset_pool() - swap_delta.amount
+        );
+
+        // Swap parachain asset back to main network currency
+        let (swap_delta_back, _, _) = exchange_after_swap
+            .calculate_second_to_first_asset_swap(swap_delta.amount, None, None)
+            .unwrap();
+
+        // Ensure round trip never returns more than initially provided
+        assert!(swap_delta_back.amount < asset_in_amount);
+
+        assert_ok!(emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            swap_delta.amount,
+            Asset::MainNetworkCurrency,
+            swap_delta_back.amount,
+            SecondAccountId::get()
+        ));
+    })
+}
+
+#[test]
+fn weighted_pool_flash_swap() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_weighted_exchange();
+
+        let asset_out_amount = 10_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), 20_000);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Calculate main network currency amount, needed to repay flash swap
+        let asset_in_amount = exchange
+            .calculate_first_asset_flash_swap_repayment(asset_out_amount, None)
+            .unwrap();
+
+        // Borrow parachain asset and repay it with main network currency
+        assert_ok!(emulate_flash_swap(
+            SecondAccountId::get(),
+            Asset::ParachainAsset(dex_para_asset_id),
+            asset_out_amount,
+            Asset::MainNetworkCurrency,
+            asset_in_amount
+        ));
+
+        assert_eq!(
+            asset_balances(SecondAccountId::get(), dex_para_asset_id),
+            asset_out_amount
+        );
+
+        assert_eq!(
+            Balances::free_balance(SecondAccountId::get()),
+            20_000 - asset_in_amount
+        );
+    })
+}
+
+#[test]
+fn weighted_pool_invalid_weights() {
+    with_test_externalities(|| {
+        let asset_id = get_next_asset_id();
+
+        // Emulate xcmp message
+        emulate_xcmp_message(FirstParaId::get(), FirstAccountId::get(), 40_0000, Some(5));
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), 10_0000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to initialize weighted exchange with total weight above max allowed
+        let initialize_new_exchange_result = initialize_new_exchange_with_curve(
+            FirstAccountId::get(),
+            // previosuly mapped parachain asset representation
+            Asset::ParachainAsset(asset_id),
+            40_0000,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            PoolCurve::Weighted(80, 80),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            initialize_new_exchange_result,
+            pallet_subdex::Error::<Test>::InvalidWeights,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn weighted_pool_swap_exact_to_zero_pools_ratio() {
+    with_test_externalities(|| {
+        let dex_para_asset_id = setup_weighted_exchange();
+
+        // Swap amount, making pools ratio round down to zero in fixed point representation
+        let asset_in_amount = 1_000_000_000_000_000_000_000_000;
+
+        // Emulate downward message
+        emulate_downward_message(SecondAccountId::get(), asset_in_amount);
+
+        let exchange = dex_exchanges(
+            Asset::MainNetworkCurrency,
+            Asset::ParachainAsset(dex_para_asset_id),
+        );
+
+        // Ensure swap calculation fails instead of panicking
+        let swap_calculation_result: Result<(), DispatchError> = exchange
+            .calculate_first_to_second_asset_swap(asset_in_amount, None, None)
+            .map(|_| ())
+            .map_err(|error| error.into());
+
+        assert_eq!(
+            swap_calculation_result,
+            Err(pallet_subdex::Error::<Test>::InsufficientPool.into())
+        );
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to swap huge main network currency amount against tiny parachain asset reserve
+        let swap_exact_to_result = emulate_swap_exact_to(
+            SecondAccountId::get(),
+            Asset::MainNetworkCurrency,
+            asset_in_amount,
+            Asset::ParachainAsset(dex_para_asset_id),
+            0,
+            SecondAccountId::get(),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            swap_exact_to_result,
+            pallet_subdex::Error::<Test>::InsufficientPool,
+            number_of_events_before_call,
+        );
+    })
+}
+
+#[test]
+fn weighted_pool_initialize_exchange_zero_pools_ratio() {
+    with_test_externalities(|| {
+        let asset_id = get_next_asset_id();
+
+        // Parachain asset pool, making pools ratio round down to zero in fixed point representation
+        let para_asset_amount = 1_000_000_000_000_000_000_000_000;
+
+        // Emulate xcmp message
+        emulate_xcmp_message(
+            FirstParaId::get(),
+            FirstAccountId::get(),
+            para_asset_amount,
+            Some(5),
+        );
+
+        // Emulate downward message
+        emulate_downward_message(FirstAccountId::get(), 10_0000);
+
+        // Runtime tested state before call
+
+        // Events number before tested call
+        let number_of_events_before_call = System::events().len();
+
+        // Make an attempt to initialize weighted exchange with tiny main network currency reserve
+        let initialize_new_exchange_result = initialize_new_exchange_with_curve(
+            FirstAccountId::get(),
+            // previosuly mapped parachain asset representation
+            Asset::ParachainAsset(asset_id),
+            para_asset_amount,
+            Asset::MainNetworkCurrency,
+            10_0000,
+            PoolCurve::Weighted(80, 20),
+        );
+
+        // Failure checked
+        assert_subdex_failure(
+            initialize_new_exchange_result,
+            pallet_subdex::Error::<Test>::InsufficientPool,
+            number_of_events_before_call,
+        );
+    })
+}
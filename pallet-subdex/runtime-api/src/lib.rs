@@ -0,0 +1,25 @@
+//! Runtime API definition for pallet-subdex.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_subdex::Asset;
+
+sp_api::decl_runtime_apis! {
+    /// Api, used to preview dex operations before they are submitted
+    pub trait SubdexApi<AccountId, AssetId, Balance, SwapSimulation> where
+        AccountId: Codec,
+        AssetId: Codec + Default + core::fmt::Debug + Ord + Copy,
+        Balance: Codec,
+        SwapSimulation: Codec,
+    {
+        /// Dry run swap of some asset exact amount to another asset, performed by a given sender (if provided).
+        /// Returns None, if swap can not be performed (e.g. exchange does not exist).
+        fn simulate_swap(
+            asset_in: Asset<AssetId>,
+            asset_in_amount: Balance,
+            asset_out: Asset<AssetId>,
+            sender: Option<AccountId>,
+        ) -> Option<SwapSimulation>;
+    }
+}
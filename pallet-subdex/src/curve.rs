@@ -0,0 +1,409 @@
+use super::*;
+use crate::math::{
+    div_ceil, fixed_exp_neg, fixed_neg_ln, fixed_one, integer_sqrt, mul_div, mul_div_ceil,
+};
+use sp_core::U256;
+use sp_runtime::traits::{AtLeast32BitUnsigned, UniqueSaturatedInto};
+
+/// Max amplification coefficient, allowed for StableSwap pools
+pub const MAX_AMPLIFICATION: u32 = 1_000_000;
+
+/// Max total weight of both weighted pool assets
+pub const MAX_TOTAL_WEIGHT: u32 = 100;
+
+// Fixed point margin, covering logarithm and exponent approximation error in favour of the pool
+const POW_APPROXIMATION_MARGIN: u128 = 1_000_000;
+
+// Max number of Newton`s method iterations, used to calculate StableSwap invariant and pools
+const MAX_ITERATIONS: usize = 255;
+
+/// Curve, used to price swaps between first and second asset pools
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoolCurve {
+    /// Constant product curve (x * y = k)
+    ConstantProduct,
+    /// StableSwap curve with a given amplification coefficient, used for correlated assets
+    StableSwap(u32),
+    /// Weighted constant product curve (x^first_weight * y^second_weight = k) with given first and second asset weights
+    Weighted(u32, u32),
+}
+
+impl Default for PoolCurve {
+    fn default() -> Self {
+        Self::ConstantProduct
+    }
+}
+
+impl PoolCurve {
+    /// Ensure curve parameters are valid
+    pub fn ensure_valid<T: Trait>(&self) -> Result<(), Error<T>> {
+        match self {
+            Self::ConstantProduct => (),
+            Self::StableSwap(amplification) => ensure!(
+                *amplification > 0 && *amplification <= MAX_AMPLIFICATION,
+                Error::<T>::InvalidAmplificationCoefficient
+            ),
+            Self::Weighted(first_weight, second_weight) => ensure!(
+                *first_weight > 0
+                    && *second_weight > 0
+                    && first_weight.saturating_add(*second_weight) <= MAX_TOTAL_WEIGHT,
+                Error::<T>::InvalidWeights
+            ),
+        }
+        Ok(())
+    }
+
+    /// Curve with first and second asset roles swapped
+    pub fn reversed(&self) -> Self {
+        match self {
+            Self::Weighted(first_weight, second_weight) => {
+                Self::Weighted(*second_weight, *first_weight)
+            }
+            curve => *curve,
+        }
+    }
+
+    /// Max opposite pool overestimation, caused by curve approximation error margin.
+    /// Used to calculate amounts, which should pass subsequent opposite pool calculation.
+    pub fn calculate_approximation_margin<T: Trait>(
+        &self,
+        opposite_pool: U256,
+    ) -> Result<U256, Error<T>> {
+        match self {
+            Self::Weighted(..) => Ok(mul_div_ceil::<T>(
+                opposite_pool,
+                U256::from(POW_APPROXIMATION_MARGIN),
+                fixed_one(),
+            )? + U256::one()),
+            _ => Ok(U256::zero()),
+        }
+    }
+
+    /// Calculate initial shares amount for a newly launched pool
+    pub fn calculate_initial_shares<T: Trait>(
+        &self,
+        first_asset_pool: U256,
+        second_asset_pool: U256,
+    ) -> Result<U256, Error<T>> {
+        match self {
+            Self::ConstantProduct => Ok(integer_sqrt(
+                first_asset_pool
+                    .checked_mul(second_asset_pool)
+                    .ok_or(Error::<T>::OverflowOccured)?,
+            )),
+            Self::StableSwap(amplification) => {
+                calculate_stable_swap_invariant(*amplification, first_asset_pool, second_asset_pool)
+            }
+            // Weighted geometric mean of both pools: y * (x / y)^(first_weight / total_weight), where x <= y
+            Self::Weighted(first_weight, second_weight) => {
+                let (lower_pool, lower_pool_weight, upper_pool) =
+                    if first_asset_pool <= second_asset_pool {
+                        (first_asset_pool, *first_weight, second_asset_pool)
+                    } else {
+                        (second_asset_pool, *second_weight, first_asset_pool)
+                    };
+                ensure!(!lower_pool.is_zero(), Error::<T>::InsufficientPool);
+
+                let pow = calculate_pow(
+                    mul_div::<T>(lower_pool, fixed_one(), upper_pool)?,
+                    lower_pool_weight,
+                    first_weight.saturating_add(*second_weight),
+                )?;
+
+                mul_div::<T>(
+                    upper_pool,
+                    pow.saturating_sub(U256::from(POW_APPROXIMATION_MARGIN)),
+                    fixed_one(),
+                )
+            }
+        }
+    }
+
+    /// Calculate opposite pool amount after given pool changed to a new amount, preserving curve invariant.
+    /// Result is rounded up in favour of the pool.
+    /// Weighted curve first weight is treated as given pool weight, so curve should be reversed, when second asset pool changed.
+    pub fn calculate_opposite_pool<T: Trait>(
+        &self,
+        pool: U256,
+        opposite_pool: U256,
+        new_pool: U256,
+    ) -> Result<U256, Error<T>> {
+        match self {
+            Self::ConstantProduct => div_ceil::<T>(
+                pool.checked_mul(opposite_pool)
+                    .ok_or(Error::<T>::OverflowOccured)?,
+                new_pool,
+            ),
+            Self::StableSwap(amplification) => {
+                let invariant =
+                    calculate_stable_swap_invariant(*amplification, pool, opposite_pool)?;
+                let new_opposite_pool =
+                    calculate_stable_swap_pool(*amplification, new_pool, invariant)?
+                        .checked_add(U256::one())
+                        .ok_or(Error::<T>::OverflowOccured)?;
+
+                // Extra unit, added in favour of the pool, should never make pool growth produce opposite pool growth
+                if new_pool >= pool {
+                    Ok(new_opposite_pool.min(opposite_pool))
+                } else {
+                    Ok(new_opposite_pool)
+                }
+            }
+            // y' = y * (x / x')^(first_weight / second_weight)
+            Self::Weighted(pool_weight, opposite_pool_weight) => {
+                ensure!(!new_pool.is_zero(), Error::<T>::InsufficientPool);
+                if new_pool >= pool {
+                    let pow = calculate_pow(
+                        mul_div::<T>(pool, fixed_one(), new_pool)?,
+                        *pool_weight,
+                        *opposite_pool_weight,
+                    )?;
+                    // Approximation margin should never make pool growth produce opposite pool growth
+                    Ok(mul_div_ceil::<T>(
+                        opposite_pool,
+                        pow + U256::from(POW_APPROXIMATION_MARGIN),
+                        fixed_one(),
+                    )?
+                    .min(opposite_pool))
+                } else {
+                    let pow = calculate_pow(
+                        mul_div::<T>(new_pool, fixed_one(), pool)?,
+                        *pool_weight,
+                        *opposite_pool_weight,
+                    )?
+                    .checked_sub(U256::from(POW_APPROXIMATION_MARGIN))
+                    .filter(|pow| !pow.is_zero())
+                    .ok_or(Error::<T>::InsufficientPool)?;
+                    mul_div_ceil::<T>(opposite_pool, fixed_one(), pow)
+                }
+            }
+        }
+    }
+}
+
+// Calculate fixed point base^(nominator / denominator) for base in range (0, 1].
+// Zero base (e.g. pools ratio, rounded down to zero) is rejected, as opposite pool can not be priced.
+fn calculate_pow<T: Trait>(base: U256, nominator: u32, denominator: u32) -> Result<U256, Error<T>> {
+    let exponent = mul_div::<T>(
+        fixed_neg_ln(base).ok_or(Error::<T>::InsufficientPool)?,
+        U256::from(nominator),
+        U256::from(denominator),
+    )?;
+    Ok(fixed_exp_neg(exponent))
+}
+
+// Calculate StableSwap invariant D for two pools, solving
+// A * n^n * (x + y) + D = A * D * n^n + D^(n + 1) / (n^n * x * y), where n = 2
+fn calculate_stable_swap_invariant<T: Trait>(
+    amplification: u32,
+    first_asset_pool: U256,
+    second_asset_pool: U256,
+) -> Result<U256, Error<T>> {
+    let sum = first_asset_pool
+        .checked_add(second_asset_pool)
+        .ok_or(Error::<T>::OverflowOccured)?;
+    if sum.is_zero() {
+        return Ok(U256::zero());
+    }
+    ensure!(
+        !first_asset_pool.is_zero() && !second_asset_pool.is_zero(),
+        Error::<T>::InsufficientPool
+    );
+
+    let two = U256::from(2);
+    let amplification = U256::from(amplification) * U256::from(4);
+
+    let mut invariant = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // D^3 / (4 * x * y)
+        let invariant_product = invariant
+            .checked_mul(invariant)
+            .map(|result| result.checked_div(first_asset_pool * two))
+            .flatten()
+            .map(|result| result.checked_mul(invariant))
+            .flatten()
+            .map(|result| result.checked_div(second_asset_pool * two))
+            .flatten()
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+        let previous_invariant = invariant;
+
+        let numerator = amplification
+            .checked_mul(sum)
+            .map(|result| result.checked_add(invariant_product * two))
+            .flatten()
+            .map(|result| result.checked_mul(invariant))
+            .flatten()
+            .ok_or(Error::<T>::OverflowOccured)?;
+
+        let denominator = (amplification - U256::one())
+            .checked_mul(invariant)
+            .map(|result| result.checked_add(invariant_product * U256::from(3)))
+            .flatten()
+            .ok_or(Error::<T>::OverflowOccured)?;
+
+        invariant = numerator
+            .checked_div(denominator)
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+        if abs_diff(invariant, previous_invariant) <= U256::one() {
+            return Ok(invariant);
+        }
+    }
+    Err(Error::<T>::CurveCalculationNotConverged)
+}
+
+// Calculate StableSwap pool amount, corresponding to the given opposite pool amount and invariant D (rounded down)
+fn calculate_stable_swap_pool<T: Trait>(
+    amplification: u32,
+    opposite_pool: U256,
+    invariant: U256,
+) -> Result<U256, Error<T>> {
+    ensure!(!opposite_pool.is_zero(), Error::<T>::InsufficientPool);
+
+    let two = U256::from(2);
+    let amplification = U256::from(amplification) * U256::from(4);
+
+    // D^3 / (4 * x * A * n^n)
+    let c = invariant
+        .checked_mul(invariant)
+        .map(|result| result.checked_div(opposite_pool * two))
+        .flatten()
+        .map(|result| result.checked_mul(invariant))
+        .flatten()
+        .map(|result| result.checked_div(amplification * two))
+        .flatten()
+        .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+    // x + D / (A * n^n)
+    let b = opposite_pool
+        .checked_add(invariant / amplification)
+        .ok_or(Error::<T>::OverflowOccured)?;
+
+    let mut pool = invariant;
+    for _ in 0..MAX_ITERATIONS {
+        let previous_pool = pool;
+
+        let numerator = pool
+            .checked_mul(pool)
+            .map(|result| result.checked_add(c))
+            .flatten()
+            .ok_or(Error::<T>::OverflowOccured)?;
+
+        let denominator = (pool * two)
+            .checked_add(b)
+            .map(|result| result.checked_sub(invariant))
+            .flatten()
+            .filter(|result| !result.is_zero())
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+        pool = numerator / denominator;
+
+        if abs_diff(pool, previous_pool) <= U256::one() {
+            return Ok(pool);
+        }
+    }
+    Err(Error::<T>::CurveCalculationNotConverged)
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Liquidity bootstrapping schedule, used to shift weighted pool weights linearly over a given blocks range
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct WeightSchedule<BlockNumber> {
+    // first and second asset weights, used until start block
+    pub start_weights: (u32, u32),
+    // first and second asset weights, used since end block
+    pub end_weights: (u32, u32),
+    // block, weights shifting starts at
+    pub start_block: BlockNumber,
+    // block, weights shifting ends at
+    pub end_block: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> WeightSchedule<BlockNumber> {
+    pub fn new(
+        start_weights: (u32, u32),
+        end_weights: (u32, u32),
+        start_block: BlockNumber,
+        end_block: BlockNumber,
+    ) -> Self {
+        Self {
+            start_weights,
+            end_weights,
+            start_block,
+            end_block,
+        }
+    }
+
+    /// Ensure schedule is valid and has not ended yet at a given block
+    pub fn ensure_valid<T: Trait>(&self, now: BlockNumber) -> Result<(), Error<T>> {
+        ensure!(
+            self.start_block < self.end_block && now < self.end_block,
+            Error::<T>::InvalidWeightSchedule
+        );
+        PoolCurve::Weighted(self.start_weights.0, self.start_weights.1).ensure_valid()?;
+        PoolCurve::Weighted(self.end_weights.0, self.end_weights.1).ensure_valid()
+    }
+
+    /// Schedule with first and second asset roles swapped
+    pub fn reversed(&self) -> Self {
+        Self::new(
+            (self.start_weights.1, self.start_weights.0),
+            (self.end_weights.1, self.end_weights.0),
+            self.start_block,
+            self.end_block,
+        )
+    }
+
+    /// Check whether schedule has ended at a given block
+    pub fn is_ended(&self, now: BlockNumber) -> bool {
+        now >= self.end_block
+    }
+
+    /// Weighted curve, corresponding to a given block
+    pub fn curve_at(&self, now: BlockNumber) -> PoolCurve {
+        if now <= self.start_block {
+            return PoolCurve::Weighted(self.start_weights.0, self.start_weights.1);
+        }
+        if self.is_ended(now) {
+            return PoolCurve::Weighted(self.end_weights.0, self.end_weights.1);
+        }
+
+        let elapsed: u128 = (now - self.start_block).unique_saturated_into();
+        let duration: u128 = (self.end_block - self.start_block).unique_saturated_into();
+
+        // Linear interpolation between start and end weight
+        let interpolate = |start_weight: u32, end_weight: u32| -> u32 {
+            let (start_weight, end_weight) = (u128::from(start_weight), u128::from(end_weight));
+            let weight = if end_weight >= start_weight {
+                start_weight + (end_weight - start_weight) * elapsed / duration
+            } else {
+                start_weight - (start_weight - end_weight) * elapsed / duration
+            };
+            weight as u32
+        };
+
+        PoolCurve::Weighted(
+            interpolate(self.start_weights.0, self.end_weights.0),
+            interpolate(self.start_weights.1, self.end_weights.1),
+        )
+    }
+
+    /// Curve, used by exchange after schedule ended: constant product one for equal end weights, weighted one otherwise
+    pub fn final_curve(&self) -> PoolCurve {
+        if self.end_weights.0 == self.end_weights.1 {
+            PoolCurve::ConstantProduct
+        } else {
+            PoolCurve::Weighted(self.end_weights.0, self.end_weights.1)
+        }
+    }
+}
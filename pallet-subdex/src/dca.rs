@@ -0,0 +1,38 @@
+use super::*;
+use sp_runtime::traits::CheckedMul;
+
+/// Dollar cost averaging order identifier
+pub type DcaOrderId = u64;
+
+/// Dollar cost averaging order: recurring swap of asset in fixed amount, funds for all periods are reserved up front
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct DcaOrder<T: Trait> {
+    // order owner, receives swaps output and remaining funds refund
+    pub owner: T::AccountId,
+    // asset, swapped each period
+    pub asset_in: Asset<T::AssetId>,
+    // asset, received each period
+    pub asset_out: Asset<T::AssetId>,
+    // asset in amount, swapped each period
+    pub amount_per_period: BalanceOf<T>,
+    // number of blocks between subsequent swaps
+    pub period_blocks: T::BlockNumber,
+    // number of swaps left to be performed
+    pub periods_left: u32,
+    // min asset out amount, expected from each swap
+    pub min_out_per_period: BalanceOf<T>,
+    // block, the next swap is scheduled at
+    pub next_execution: T::BlockNumber,
+    // order deposit
+    pub deposit: BalanceOf<T>,
+}
+
+impl<T: Trait> DcaOrder<T> {
+    /// Asset in amount, reserved for swaps left to be performed
+    pub fn remaining_amount(&self) -> Result<BalanceOf<T>, Error<T>> {
+        self.amount_per_period
+            .checked_mul(&self.periods_left.into())
+            .ok_or(Error::<T>::OverflowOccured)
+    }
+}
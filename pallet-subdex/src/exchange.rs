@@ -1,7 +1,10 @@
 use super::*;
-use sp_runtime::traits::IntegerSquareRoot;
+use crate::math::{fixed_one, mul_div, mul_div_ceil, to_balance, to_u256};
+use sp_core::U256;
+use sp_runtime::{PerThing, Permill};
 
 /// Structure, used to represent exchange pool
+/// Layout is not migrated yet, as pallet is not launched on any live chain (pre-launch, fresh genesis required).
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
 pub struct Exchange<T: Trait> {
@@ -9,18 +12,26 @@ pub struct Exchange<T: Trait> {
     first_asset_pool: BalanceOf<T>,
     // second asset pool
     second_asset_pool: BalanceOf<T>,
-    // first and second asset pool invariant
-    pub invariant: BalanceOf<T>,
     // total pool shares
     pub total_shares: BalanceOf<T>,
     // last timestamp, after pool update performed, needed for time_elapsed calculation
     pub last_timestamp: T::IMoment,
-    // first_asset_pool / second_asset_pool * time_elapsed
-    pub price1_cumulative_last: BalanceOf<T>,
-    // second_asset_pool / first_asset_pool * time_elapsed
-    pub price2_cumulative_last: BalanceOf<T>,
+    // first_asset_pool / second_asset_pool * time_elapsed (fixed point, wraps on overflow)
+    pub price1_cumulative_last: U256,
+    // second_asset_pool / first_asset_pool * time_elapsed (fixed point, wraps on overflow)
+    pub price2_cumulative_last: U256,
+    // timestamp and price1 cumulative, time weighted average price is measured from
+    pub price_observation: (T::IMoment, U256),
+    // first_asset_pool / second_asset_pool time weighted average over the last completed period (fixed point)
+    pub price1_average: Option<U256>,
+    // relative change between the two last time weighted average prices, used to scale dynamic fee rate
+    pub volatility: Permill,
     // individual shares
     shares: BTreeMap<T::AccountId, BalanceOf<T>>,
+    // curve, used to price swaps
+    curve: PoolCurve,
+    // liquidity bootstrapping weights schedule, used instead of curve until it ends
+    weight_schedule: Option<WeightSchedule<T::BlockNumber>>,
 }
 
 impl<T: Trait> Default for Exchange<T> {
@@ -28,12 +39,16 @@ impl<T: Trait> Default for Exchange<T> {
         Self {
             first_asset_pool: BalanceOf::<T>::default(),
             second_asset_pool: BalanceOf::<T>::default(),
-            invariant: BalanceOf::<T>::default(),
             total_shares: BalanceOf::<T>::default(),
             last_timestamp: T::IMoment::default(),
-            price1_cumulative_last: BalanceOf::<T>::default(),
-            price2_cumulative_last: BalanceOf::<T>::default(),
+            price1_cumulative_last: U256::zero(),
+            price2_cumulative_last: U256::zero(),
+            price_observation: (T::IMoment::default(), U256::zero()),
+            price1_average: None,
+            volatility: Permill::default(),
             shares: BTreeMap::new(),
+            curve: PoolCurve::default(),
+            weight_schedule: None,
         }
     }
 }
@@ -64,71 +79,150 @@ impl<T: Trait> SwapDelta<T> {
     }
 }
 
+/// Fee amount, charged to a given account (treasury or referrer) as a part of joint fee
+pub type FeeCharge<T> = Option<(BalanceOf<T>, <T as system::Trait>::AccountId)>;
+
+/// Swap dry run result, used to preview swap before it is submitted
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct SwapSimulation<T: Trait> {
+    // new first and second asset pools and asset out amount
+    pub swap_delta: SwapDelta<T>,
+    // fee part, left in the pool for liquidity providers
+    pub lp_fee: BalanceOf<T>,
+    // fee part, charged to the treasury (if enabled)
+    pub treasury_fee: Option<BalanceOf<T>>,
+    // relative decrease of asset out price (in terms of asset in), caused by swap
+    pub price_impact: Permill,
+    // asset out amount per asset in amount, swap is actually executed at, including fee (fixed point)
+    pub effective_price: U256,
+}
+
 impl<T: Trait> Exchange<T> {
-    // Calculate min fee, used to substract from initial shares amount, based on balances type size set
+    // Calculate min fee (min liquidity), used to substract from initial shares amount, based on balances type bit width.
+    // These shares are not credited to anyone, so they remain permanently locked in the pool.
     fn get_min_fee() -> BalanceOf<T> {
-        match core::mem::size_of::<BalanceOf<T>>() {
-            size if size <= 64 => 1.into(),
+        match core::mem::size_of::<BalanceOf<T>>() * 8 {
+            bits if bits <= 64 => 1.into(),
             // cosider 112 instead
-            size if size > 64 && size < 128 => 10.into(),
+            bits if bits > 64 && bits < 128 => 10.into(),
             _ => (10 * 10 * 10).into(),
         }
     }
 
+    /// First asset pool amount
+    pub fn first_asset_pool(&self) -> BalanceOf<T> {
+        self.first_asset_pool
+    }
+
+    /// Second asset pool amount
+    pub fn second_asset_pool(&self) -> BalanceOf<T> {
+        self.second_asset_pool
+    }
+
+    /// Amount of shares, owned by given account
+    pub fn shares(&self, who: &T::AccountId) -> BalanceOf<T> {
+        self.shares.get(who).copied().unwrap_or_default()
+    }
+
+    /// Curve, used to price swaps at the current block
+    pub fn curve(&self) -> PoolCurve {
+        if let Some(weight_schedule) = self.weight_schedule {
+            weight_schedule.curve_at(<system::Module<T>>::block_number())
+        } else {
+            self.curve
+        }
+    }
+
+    /// Liquidity bootstrapping weights schedule, if it has not ended yet
+    pub fn weight_schedule(&self) -> Option<WeightSchedule<T::BlockNumber>> {
+        self.weight_schedule
+    }
+
+    /// Start liquidity bootstrapping weights schedule, weights are shifted on each subsequent swap
+    pub fn start_weight_schedule(
+        &mut self,
+        weight_schedule: WeightSchedule<T::BlockNumber>,
+    ) -> Result<(), Error<T>> {
+        let now = <system::Module<T>>::block_number();
+        weight_schedule.ensure_valid(now)?;
+        self.curve = weight_schedule.curve_at(now);
+        self.weight_schedule = Some(weight_schedule);
+        Ok(())
+    }
+
+    /// First and second asset pools product, calculated in U256 to avoid overflow on large pools.
+    /// Equals to zero only when exchange does not exist.
+    pub fn invariant(&self) -> U256 {
+        to_u256::<T>(self.first_asset_pool) * to_u256::<T>(self.second_asset_pool)
+    }
+
     /// Initialize new exchange
     pub fn initialize_new(
         first_asset_amount: BalanceOf<T>,
         second_asset_amount: BalanceOf<T>,
         sender: T::AccountId,
+        curve: PoolCurve,
     ) -> Result<(Self, BalanceOf<T>), Error<T>> {
         let mut shares_map = BTreeMap::new();
         let min_fee = Self::get_min_fee();
 
-        // Calculate initial shares amount, based on formula
-        let initial_shares = first_asset_amount
-            .checked_mul(&second_asset_amount)
-            .map(|result| result.integer_sqrt_checked())
-            .flatten()
-            // substract min fee amount
-            .map(|sqrt_result| sqrt_result.checked_sub(&min_fee))
-            .flatten()
+        curve.ensure_valid()?;
+
+        // Calculate total shares amount, based on curve used
+        let total_shares = to_balance::<T>(curve.calculate_initial_shares(
+            to_u256::<T>(first_asset_amount),
+            to_u256::<T>(second_asset_amount),
+        )?)?;
+
+        // Substract min fee amount, it stays locked, so the pool can never be drained to an empty state
+        let initial_shares = total_shares
+            .checked_sub(&min_fee)
             .ok_or(Error::<T>::UnderflowOccured)?;
 
         shares_map.insert(sender, initial_shares);
+        let now: T::IMoment = <pallet_timestamp::Module<T>>::get().into();
         let exchange = Self {
             first_asset_pool: first_asset_amount,
             second_asset_pool: second_asset_amount,
-            invariant: first_asset_amount
-                .checked_mul(&second_asset_amount)
-                .ok_or(Error::<T>::UnderflowOrOverflowOccured)?,
-            total_shares: initial_shares,
+            total_shares,
             shares: shares_map,
-            last_timestamp: <pallet_timestamp::Module<T>>::get().into(),
-            price1_cumulative_last: BalanceOf::<T>::default(),
-            price2_cumulative_last: BalanceOf::<T>::default(),
+            last_timestamp: now,
+            price1_cumulative_last: U256::zero(),
+            price2_cumulative_last: U256::zero(),
+            price_observation: (now, U256::zero()),
+            price1_average: None,
+            volatility: Permill::default(),
+            curve,
+            weight_schedule: None,
         };
         Ok((exchange, initial_shares))
     }
 
-    // Calculate first to second asset swap delta
+    // Calculate first to second asset swap delta.
+    // Charged fees (treasury and referral) are paid out of asset in amount, so they never enter the pool.
     fn perform_first_to_second_asset_swap_calculation(
         &self,
         exchange_fee: BalanceOf<T>,
+        charged_fee: BalanceOf<T>,
         first_asset_amount: BalanceOf<T>,
     ) -> Result<SwapDelta<T>, Error<T>> {
-        let new_first_asset_pool = self
-            .first_asset_pool
-            .checked_add(&first_asset_amount)
+        let new_first_asset_pool = first_asset_amount
+            .checked_sub(&charged_fee)
+            .ok_or(Error::<T>::UnderflowOccured)?
+            .checked_add(&self.first_asset_pool)
             .ok_or(Error::<T>::OverflowOccured)?;
 
         let temp_first_asset_pool = new_first_asset_pool
             .checked_sub(&exchange_fee)
             .ok_or(Error::<T>::UnderflowOccured)?;
 
-        let new_second_asset_pool = self
-            .invariant
-            .checked_div(&temp_first_asset_pool)
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        // Round new pool up, so that asset out amount is rounded down in favour of the pool
+        let new_second_asset_pool = to_balance::<T>(self.curve().calculate_opposite_pool(
+            to_u256::<T>(self.first_asset_pool),
+            to_u256::<T>(self.second_asset_pool),
+            to_u256::<T>(temp_first_asset_pool),
+        )?)?;
 
         let second_asset_amount = self
             .second_asset_pool
@@ -142,57 +236,190 @@ impl<T: Trait> Exchange<T> {
         ))
     }
 
-    /// Calculate first to second asset swap delta and treasury fee (if enabled)
-    pub fn calculate_first_to_second_asset_swap(
+    /// Joint fee rate nominator and denominator. When dynamic fee is enabled,
+    /// fee rate is scaled according to exchange volatility, fixed fee rate is used otherwise.
+    /// Fee discount of a given sender (if set) is applied on top.
+    pub fn fee_rate(&self, sender: Option<&T::AccountId>) -> (U256, U256) {
+        let (fee_rate_nominator, fee_rate_denominator) =
+            if let Some(dynamic_fee) = DynamicFeeParams::get() {
+                (
+                    U256::from(dynamic_fee.fee_rate(self.volatility).deconstruct()),
+                    U256::from(Permill::ACCURACY),
+                )
+            } else {
+                (
+                    to_u256::<T>(T::FeeRateNominator::get()),
+                    to_u256::<T>(T::FeeRateDenominator::get()),
+                )
+            };
+
+        if let Some(fee_discount) = sender.map(<Module<T>>::fee_exemptions).flatten() {
+            (
+                fee_rate_nominator * U256::from(Permill::ACCURACY - fee_discount.deconstruct()),
+                fee_rate_denominator * U256::from(Permill::ACCURACY),
+            )
+        } else {
+            (fee_rate_nominator, fee_rate_denominator)
+        }
+    }
+
+    /// Calculate joint fee (both exchange fee and treasury fee, if enabled) for a given asset in amount,
+    /// swapped by a given sender. Joint fee is rounded up in favour of the pool.
+    pub fn calculate_joint_fee(
         &self,
-        first_asset_amount: BalanceOf<T>,
-    ) -> Result<(SwapDelta<T>, Option<(BalanceOf<T>, T::AccountId)>), Error<T>> {
-        let fee = T::FeeRateNominator::get()
-            .checked_mul(&first_asset_amount)
-            .map(|result| result.checked_div(&T::FeeRateDenominator::get()))
-            .flatten()
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        asset_in_amount: BalanceOf<T>,
+        sender: Option<&T::AccountId>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        let (fee_rate_nominator, fee_rate_denominator) = self.fee_rate(sender);
+        to_balance::<T>(mul_div_ceil::<T>(
+            fee_rate_nominator,
+            to_u256::<T>(asset_in_amount),
+            fee_rate_denominator,
+        )?)
+    }
 
-        if let Ok(dex_treasury) = <DEXTreasury<T>>::try_get() {
-            let treasury_fee = dex_treasury
-                .treasury_fee_rate_nominator
-                .checked_mul(&fee)
-                .map(|result| result.checked_div(&dex_treasury.treasury_fee_rate_denominator))
-                .flatten()
-                .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+    /// Calculate relative decrease of asset out price (in terms of asset in), caused by swap with a given delta.
+    /// Price is represented by pools ratio, so that its relative change does not depend on curve weights.
+    pub fn calculate_price_impact(
+        &self,
+        swap_delta: &SwapDelta<T>,
+        first_to_second: bool,
+    ) -> Result<Permill, Error<T>> {
+        let (asset_in_pool, asset_out_pool, new_asset_in_pool, new_asset_out_pool) =
+            if first_to_second {
+                (
+                    self.first_asset_pool,
+                    self.second_asset_pool,
+                    swap_delta.first_asset_pool,
+                    swap_delta.second_asset_pool,
+                )
+            } else {
+                (
+                    self.second_asset_pool,
+                    self.first_asset_pool,
+                    swap_delta.second_asset_pool,
+                    swap_delta.first_asset_pool,
+                )
+            };
+
+        // (new_asset_out_pool / new_asset_in_pool) / (asset_out_pool / asset_in_pool)
+        let price_ratio = mul_div::<T>(
+            to_u256::<T>(new_asset_out_pool) * to_u256::<T>(asset_in_pool),
+            U256::from(Permill::ACCURACY),
+            to_u256::<T>(asset_out_pool) * to_u256::<T>(new_asset_in_pool),
+        )?
+        .min(U256::from(Permill::ACCURACY))
+        .as_u32();
+
+        Ok(Permill::from_parts(Permill::ACCURACY - price_ratio))
+    }
 
-            let exchange_fee = fee - treasury_fee;
+    // Split joint fee into treasury fee (if enabled), referral fee (if referrer provided) and exchange fee, left in the pool.
+    // Returns exchange fee, sum of fees charged out of the pool, treasury fee and referral fee.
+    fn split_joint_fee(
+        &self,
+        asset_in_amount: BalanceOf<T>,
+        sender: Option<&T::AccountId>,
+        referrer: Option<T::AccountId>,
+    ) -> Result<(BalanceOf<T>, BalanceOf<T>, FeeCharge<T>, FeeCharge<T>), Error<T>> {
+        let fee = self.calculate_joint_fee(asset_in_amount, sender)?;
+
+        let treasury_fee_data = if let Ok(dex_treasury) = <DEXTreasury<T>>::try_get() {
+            let treasury_fee = to_balance::<T>(mul_div::<T>(
+                to_u256::<T>(dex_treasury.treasury_fee_rate_nominator),
+                to_u256::<T>(fee),
+                to_u256::<T>(dex_treasury.treasury_fee_rate_denominator),
+            )?)?;
+            Some((treasury_fee, dex_treasury.dex_account))
+        } else {
+            None
+        };
 
-            let swap_delta = self
-                .perform_first_to_second_asset_swap_calculation(exchange_fee, first_asset_amount)?;
+        let referral_share = <Module<T>>::referral_share();
+
+        let referral_fee_data = match referrer {
+            Some(referrer) if referral_share.deconstruct() > 0 => {
+                let referral_fee = to_balance::<T>(mul_div::<T>(
+                    U256::from(referral_share.deconstruct()),
+                    to_u256::<T>(fee),
+                    U256::from(Permill::ACCURACY),
+                )?)?;
+                Some((referral_fee, referrer))
+            }
+            _ => None,
+        };
 
-            Ok((swap_delta, Some((treasury_fee, dex_treasury.dex_account))))
-        } else {
-            let swap_delta =
-                self.perform_first_to_second_asset_swap_calculation(fee, first_asset_amount)?;
-            Ok((swap_delta, None))
-        }
+        let treasury_fee = treasury_fee_data
+            .as_ref()
+            .map(|(treasury_fee, _)| *treasury_fee)
+            .unwrap_or_default();
+
+        let referral_fee = referral_fee_data
+            .as_ref()
+            .map(|(referral_fee, _)| *referral_fee)
+            .unwrap_or_default();
+
+        let charged_fee = treasury_fee
+            .checked_add(&referral_fee)
+            .ok_or(Error::<T>::OverflowOccured)?;
+
+        let exchange_fee = fee
+            .checked_sub(&charged_fee)
+            .ok_or(Error::<T>::UnderflowOccured)?;
+
+        Ok((
+            exchange_fee,
+            charged_fee,
+            treasury_fee_data,
+            referral_fee_data,
+        ))
     }
 
-    // Calculate second to first asset swap delta
+    /// Calculate first to second asset swap delta, treasury fee (if enabled) and referral fee (if referrer provided).
+    /// Fee discount of a given sender (if set) is applied to the joint fee.
+    pub fn calculate_first_to_second_asset_swap(
+        &self,
+        first_asset_amount: BalanceOf<T>,
+        sender: Option<&T::AccountId>,
+        referrer: Option<T::AccountId>,
+    ) -> Result<(SwapDelta<T>, FeeCharge<T>, FeeCharge<T>), Error<T>> {
+        let (exchange_fee, charged_fee, treasury_fee_data, referral_fee_data) =
+            self.split_joint_fee(first_asset_amount, sender, referrer)?;
+
+        let swap_delta = self.perform_first_to_second_asset_swap_calculation(
+            exchange_fee,
+            charged_fee,
+            first_asset_amount,
+        )?;
+
+        Ok((swap_delta, treasury_fee_data, referral_fee_data))
+    }
+
+    // Calculate second to first asset swap delta.
+    // Charged fees (treasury and referral) are paid out of asset in amount, so they never enter the pool.
     fn perform_second_to_first_asset_swap_calculation(
         &self,
         exchange_fee: BalanceOf<T>,
+        charged_fee: BalanceOf<T>,
         second_asset_amount: BalanceOf<T>,
     ) -> Result<SwapDelta<T>, Error<T>> {
-        let new_second_asset_pool = self
-            .second_asset_pool
-            .checked_add(&second_asset_amount)
+        let new_second_asset_pool = second_asset_amount
+            .checked_sub(&charged_fee)
+            .ok_or(Error::<T>::UnderflowOccured)?
+            .checked_add(&self.second_asset_pool)
             .ok_or(Error::<T>::OverflowOccured)?;
 
         let temp_second_asset_pool = new_second_asset_pool
             .checked_sub(&exchange_fee)
             .ok_or(Error::<T>::UnderflowOccured)?;
 
-        let new_first_asset_pool = self
-            .invariant
-            .checked_div(&temp_second_asset_pool)
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        // Round new pool up, so that asset out amount is rounded down in favour of the pool
+        let new_first_asset_pool =
+            to_balance::<T>(self.curve().reversed().calculate_opposite_pool(
+                to_u256::<T>(self.second_asset_pool),
+                to_u256::<T>(self.first_asset_pool),
+                to_u256::<T>(temp_second_asset_pool),
+            )?)?;
 
         let first_asset_amount = self
             .first_asset_pool
@@ -206,61 +433,144 @@ impl<T: Trait> Exchange<T> {
         ))
     }
 
-    /// Calculate second to first asset swap delta and treasury fee (if enabled)
+    /// Calculate second to first asset swap delta, treasury fee (if enabled) and referral fee (if referrer provided).
+    /// Fee discount of a given sender (if set) is applied to the joint fee.
     pub fn calculate_second_to_first_asset_swap(
         &self,
         second_asset_amount: BalanceOf<T>,
-    ) -> Result<(SwapDelta<T>, Option<(BalanceOf<T>, T::AccountId)>), Error<T>> {
-        let fee = T::FeeRateNominator::get()
-            .checked_mul(&second_asset_amount)
-            .map(|result| result.checked_div(&T::FeeRateDenominator::get()))
+        sender: Option<&T::AccountId>,
+        referrer: Option<T::AccountId>,
+    ) -> Result<(SwapDelta<T>, FeeCharge<T>, FeeCharge<T>), Error<T>> {
+        let (exchange_fee, charged_fee, treasury_fee_data, referral_fee_data) =
+            self.split_joint_fee(second_asset_amount, sender, referrer)?;
+
+        let swap_delta = self.perform_second_to_first_asset_swap_calculation(
+            exchange_fee,
+            charged_fee,
+            second_asset_amount,
+        )?;
+
+        Ok((swap_delta, treasury_fee_data, referral_fee_data))
+    }
+
+    // Calculate asset in amount, needed to repay flash swap of a given asset out amount (including joint fee)
+    fn calculate_flash_swap_repayment(
+        curve: PoolCurve,
+        (fee_rate_nominator, fee_rate_denominator): (U256, U256),
+        asset_in_pool: BalanceOf<T>,
+        asset_out_pool: BalanceOf<T>,
+        asset_out_amount: BalanceOf<T>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        let new_asset_out_pool = asset_out_pool
+            .checked_sub(&asset_out_amount)
+            .filter(|new_asset_out_pool| !new_asset_out_pool.is_zero())
+            .ok_or(Error::<T>::InsufficientPool)?;
+
+        // Leave room for curve approximation margin, applied during regular swap calculation
+        let target_asset_out_pool = to_u256::<T>(new_asset_out_pool)
+            .checked_sub(curve.calculate_approximation_margin(to_u256::<T>(asset_out_pool))?)
+            .filter(|target_asset_out_pool| !target_asset_out_pool.is_zero())
+            .ok_or(Error::<T>::InsufficientPool)?;
+
+        // Asset in pool amount (excluding exchange fee), needed to restore the invariant
+        let temp_asset_in_pool = curve.calculate_opposite_pool(
+            to_u256::<T>(asset_out_pool),
+            to_u256::<T>(asset_in_pool),
+            target_asset_out_pool,
+        )?;
+
+        // One extra unit covers exchange fee rounding, performed during regular swap calculation
+        let asset_in_amount_without_fee = temp_asset_in_pool
+            .checked_sub(to_u256::<T>(asset_in_pool))
+            .map(|result| result.checked_add(U256::one()))
             .flatten()
             .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
 
-        if let Ok(dex_treasury) = <DEXTreasury<T>>::try_get() {
-            let treasury_fee = dex_treasury
-                .treasury_fee_rate_nominator
-                .checked_mul(&fee)
-                .map(|result| result.checked_div(&dex_treasury.treasury_fee_rate_denominator))
-                .flatten()
-                .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
-
-            let exchange_fee = fee - treasury_fee;
+        // Gross up asset in amount with joint fee rate (rounded up in favour of the pool)
+        let fee_rate_complement = fee_rate_denominator
+            .checked_sub(fee_rate_nominator)
+            .ok_or(Error::<T>::UnderflowOccured)?;
 
-            let swap_delta = self.perform_second_to_first_asset_swap_calculation(
-                exchange_fee,
-                second_asset_amount,
-            )?;
+        to_balance::<T>(mul_div_ceil::<T>(
+            asset_in_amount_without_fee,
+            fee_rate_denominator,
+            fee_rate_complement,
+        )?)
+    }
 
-            Ok((swap_delta, Some((treasury_fee, dex_treasury.dex_account))))
-        } else {
-            let swap_delta =
-                self.perform_second_to_first_asset_swap_calculation(fee, second_asset_amount)?;
+    /// Calculate first asset amount, needed to repay flash swap of a given second asset amount (including joint fee)
+    pub fn calculate_first_asset_flash_swap_repayment(
+        &self,
+        second_asset_amount: BalanceOf<T>,
+        borrower: Option<&T::AccountId>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        Self::calculate_flash_swap_repayment(
+            self.curve().reversed(),
+            self.fee_rate(borrower),
+            self.first_asset_pool,
+            self.second_asset_pool,
+            second_asset_amount,
+        )
+    }
 
-            Ok((swap_delta, None))
-        }
+    /// Calculate second asset amount, needed to repay flash swap of a given first asset amount (including joint fee)
+    pub fn calculate_second_asset_flash_swap_repayment(
+        &self,
+        first_asset_amount: BalanceOf<T>,
+        borrower: Option<&T::AccountId>,
+    ) -> Result<BalanceOf<T>, Error<T>> {
+        Self::calculate_flash_swap_repayment(
+            self.curve(),
+            self.fee_rate(borrower),
+            self.second_asset_pool,
+            self.first_asset_pool,
+            first_asset_amount,
+        )
     }
 
-    /// Calculate costs for both first and second currencies, needed to get a given amount of shares
+    /// Calculate costs for both first and second currencies, needed to get a given amount of shares.
+    /// Costs are rounded up, so that investor never pays less than shares fair value.
+    /// Both assets are invested proportionally, so shares value is preserved for any pool curve.
     pub fn calculate_costs(
         &self,
         shares: BalanceOf<T>,
     ) -> Result<(BalanceOf<T>, BalanceOf<T>), Error<T>> {
-        let first_asset_cost = shares
-            .checked_mul(&self.first_asset_pool)
-            .map(|result| result.checked_div(&self.total_shares))
-            .flatten()
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
-
-        let second_asset_cost = shares
-            .checked_mul(&self.second_asset_pool)
-            .map(|result| result.checked_div(&self.total_shares))
-            .flatten()
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        let first_asset_cost = to_balance::<T>(mul_div_ceil::<T>(
+            to_u256::<T>(shares),
+            to_u256::<T>(self.first_asset_pool),
+            to_u256::<T>(self.total_shares),
+        )?)?;
+
+        let second_asset_cost = to_balance::<T>(mul_div_ceil::<T>(
+            to_u256::<T>(shares),
+            to_u256::<T>(self.second_asset_pool),
+            to_u256::<T>(self.total_shares),
+        )?)?;
 
         Ok((first_asset_cost, second_asset_cost))
     }
 
+    /// Calculate both first and second currencies amounts, received after a given amount of shares burned.
+    /// Amounts are rounded down, so that divestor never receives more than shares fair value.
+    pub fn calculate_divest_amounts(
+        &self,
+        shares: BalanceOf<T>,
+    ) -> Result<(BalanceOf<T>, BalanceOf<T>), Error<T>> {
+        let first_asset_amount = to_balance::<T>(mul_div::<T>(
+            to_u256::<T>(shares),
+            to_u256::<T>(self.first_asset_pool),
+            to_u256::<T>(self.total_shares),
+        )?)?;
+
+        let second_asset_amount = to_balance::<T>(mul_div::<T>(
+            to_u256::<T>(shares),
+            to_u256::<T>(self.second_asset_pool),
+            to_u256::<T>(self.total_shares),
+        )?)?;
+
+        Ok((first_asset_amount, second_asset_amount))
+    }
+
     /// Perform invest operation
     pub fn invest(
         &mut self,
@@ -293,11 +603,6 @@ impl<T: Trait> Exchange<T> {
             .second_asset_pool
             .checked_add(&second_asset_amount)
             .ok_or(Error::<T>::OverflowOccured)?;
-
-        self.invariant = self
-            .first_asset_pool
-            .checked_mul(&self.second_asset_pool)
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
         Ok(())
     }
 
@@ -313,6 +618,11 @@ impl<T: Trait> Exchange<T> {
             *share = share
                 .checked_sub(&shares)
                 .ok_or(Error::<T>::UnderflowOccured)?;
+
+            // Remove zero-share entries, so that only actual liquidity providers are stored
+            if share.is_zero() {
+                self.shares.remove(sender);
+            }
         }
 
         self.total_shares = self
@@ -329,69 +639,195 @@ impl<T: Trait> Exchange<T> {
             .second_asset_pool
             .checked_sub(&second_asset_amount)
             .ok_or(Error::<T>::UnderflowOccured)?;
+        Ok(())
+    }
+
+    // Calculate asset pools ratio (fixed point)
+    fn calculate_price(
+        asset_pool: BalanceOf<T>,
+        opposite_asset_pool: BalanceOf<T>,
+    ) -> Result<U256, Error<T>> {
+        mul_div::<T>(
+            to_u256::<T>(asset_pool),
+            fixed_one(),
+            to_u256::<T>(opposite_asset_pool),
+        )
+    }
 
-        if self.total_shares == BalanceOf::<T>::zero() {
-            self.invariant = BalanceOf::<T>::zero();
+    // Calculate relative difference between price and reference price (saturated at 100%)
+    fn calculate_price_deviation(price: U256, reference_price: U256) -> Result<Permill, Error<T>> {
+        let price_difference = if price > reference_price {
+            price - reference_price
         } else {
-            self.invariant = self
-                .first_asset_pool
-                .checked_mul(&self.second_asset_pool)
-                .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+            reference_price - price
+        };
+
+        let deviation = mul_div::<T>(
+            price_difference,
+            U256::from(Permill::ACCURACY),
+            reference_price,
+        )?
+        .min(U256::from(Permill::ACCURACY))
+        .as_u32();
+
+        Ok(Permill::from_parts(deviation))
+    }
+
+    /// Calculate relative deviation of first asset price after swap with a given delta from its current time weighted average price.
+    /// Returns None, if average price is not available yet (no period completed since exchange initialization).
+    pub fn calculate_oracle_deviation(
+        &self,
+        swap_delta: &SwapDelta<T>,
+    ) -> Result<Option<Permill>, Error<T>> {
+        if let Some(price1_average) = self.calculate_twap_price(false)? {
+            let price1 =
+                Self::calculate_price(swap_delta.first_asset_pool, swap_delta.second_asset_pool)?;
+
+            Ok(Some(Self::calculate_price_deviation(
+                price1,
+                price1_average,
+            )?))
+        } else {
+            Ok(None)
         }
-        Ok(())
     }
 
-    /// Update exchange liquidity pools with amounts provided, update cumulative price data
+    /// Calculate current time weighted average price of the swapped asset in units of the opposite one (fixed point).
+    /// Average is measured from the last observation up to now, once twap period passed since it,
+    /// average over the last completed period is used otherwise.
+    /// Returns None, if average price is not available yet (no period completed since exchange initialization).
+    pub fn calculate_twap_price(&self, first_asset_in: bool) -> Result<Option<U256>, Error<T>> {
+        let now: T::IMoment = <pallet_timestamp::Module<T>>::get().into();
+        let time_elapsed: T::IMoment = now
+            .checked_sub(&self.last_timestamp)
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+        // Accumulate current price, held since the last pools update
+        let price1_cumulative = if !time_elapsed.is_zero() {
+            let price1 = Self::calculate_price(self.first_asset_pool, self.second_asset_pool)?;
+            self.price1_cumulative_last
+                .overflowing_add(price1.overflowing_mul(to_u256::<T>(time_elapsed.into())).0)
+                .0
+        } else {
+            self.price1_cumulative_last
+        };
+
+        let (observation_timestamp, observation_price1_cumulative) = self.price_observation;
+        let period: T::IMoment = now
+            .checked_sub(&observation_timestamp)
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+
+        let price1_average = if !period.is_zero() && period >= T::TwapPeriod::get() {
+            Some(
+                price1_cumulative
+                    .overflowing_sub(observation_price1_cumulative)
+                    .0
+                    / to_u256::<T>(period.into()),
+            )
+        } else {
+            self.price1_average
+        };
+
+        // Average first_asset_pool / second_asset_pool ratio is the price of the second asset in units of the first one
+        price1_average
+            .filter(|price| !price.is_zero())
+            .map(|price1_average| {
+                if first_asset_in {
+                    mul_div::<T>(fixed_one(), fixed_one(), price1_average)
+                } else {
+                    Ok(price1_average)
+                }
+            })
+            .transpose()
+    }
+
+    /// Update exchange liquidity pools with amounts provided, update cumulative price data,
+    /// time weighted average price and volatility, once twap period since the last observation passed
     pub fn update_pools(
         &mut self,
         first_asset_pool: BalanceOf<T>,
         second_asset_pool: BalanceOf<T>,
     ) -> Result<(), Error<T>> {
-        self.first_asset_pool = first_asset_pool;
-        self.second_asset_pool = second_asset_pool;
-
         let now: T::IMoment = <pallet_timestamp::Module<T>>::get().into();
         let time_elapsed: T::IMoment = now
             .checked_sub(&self.last_timestamp)
             .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
 
-        let price1_cumulative = first_asset_pool
-            .checked_div(&second_asset_pool)
-            .map(|result| result.checked_mul(&time_elapsed.into()))
-            .flatten()
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        // Accumulate prices, held during elapsed time (before pools updated)
+        if !time_elapsed.is_zero() {
+            let time_elapsed = to_u256::<T>(time_elapsed.into());
 
-        self.price1_cumulative_last = self
-            .price1_cumulative_last
-            .checked_add(&price1_cumulative)
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+            let price1 = Self::calculate_price(self.first_asset_pool, self.second_asset_pool)?;
+            let price2 = Self::calculate_price(self.second_asset_pool, self.first_asset_pool)?;
 
-        let price2_cumulative = second_asset_pool
-            .checked_div(&first_asset_pool)
-            .map(|result| result.checked_mul(&time_elapsed.into()))
-            .flatten()
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+            self.price1_cumulative_last = self
+                .price1_cumulative_last
+                .overflowing_add(price1.overflowing_mul(time_elapsed).0)
+                .0;
 
-        self.price2_cumulative_last = self
-            .price2_cumulative_last
-            .checked_add(&price2_cumulative)
+            self.price2_cumulative_last = self
+                .price2_cumulative_last
+                .overflowing_add(price2.overflowing_mul(time_elapsed).0)
+                .0;
+        }
+
+        let (observation_timestamp, observation_price1_cumulative) = self.price_observation;
+        let period: T::IMoment = now
+            .checked_sub(&observation_timestamp)
             .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
 
+        // Refresh time weighted average price, once twap period passed since the last observation
+        if !period.is_zero() && period >= T::TwapPeriod::get() {
+            let price1_average = self
+                .price1_cumulative_last
+                .overflowing_sub(observation_price1_cumulative)
+                .0
+                / to_u256::<T>(period.into());
+
+            // Volatility is measured as a relative change of the average price between two subsequent periods
+            if let Some(previous_price1_average) =
+                self.price1_average.filter(|price| !price.is_zero())
+            {
+                self.volatility =
+                    Self::calculate_price_deviation(price1_average, previous_price1_average)?;
+            }
+
+            self.price1_average = Some(price1_average);
+            self.price_observation = (now, self.price1_cumulative_last);
+        }
+
+        self.first_asset_pool = first_asset_pool;
+        self.second_asset_pool = second_asset_pool;
         self.last_timestamp = now;
 
-        self.invariant = self
-            .first_asset_pool
-            .checked_mul(&self.second_asset_pool)
-            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+        // Convert liquidity bootstrapping pool into a regular one, once weights schedule ended
+        if let Some(weight_schedule) = self.weight_schedule {
+            if weight_schedule.is_ended(<system::Module<T>>::block_number()) {
+                self.curve = weight_schedule.final_curve();
+                self.weight_schedule = None;
+            }
+        }
         Ok(())
     }
 
-    /// Ensure new liquidity pool can be launched successfully
-    pub fn ensure_launch(&self) -> dispatch::DispatchResult {
+    /// Ensure exchange already exists
+    pub fn ensure_exists(&self) -> Result<(), Error<T>> {
+        ensure!(!self.invariant().is_zero(), Error::<T>::ExchangeNotExists);
+        Ok(())
+    }
+
+    /// Ensure exchange does not exist yet
+    pub fn ensure_not_exists(&self) -> Result<(), Error<T>> {
         ensure!(
-            self.invariant == BalanceOf::<T>::zero(),
-            Error::<T>::InvariantNotNull
+            self.invariant().is_zero(),
+            Error::<T>::ExchangeAlreadyExists
         );
+        Ok(())
+    }
+
+    /// Ensure new liquidity pool can be launched successfully
+    pub fn ensure_launch(&self) -> dispatch::DispatchResult {
+        ensure!(self.invariant().is_zero(), Error::<T>::InvariantNotNull);
         ensure!(
             self.total_shares == BalanceOf::<T>::zero(),
             Error::<T>::TotalSharesNotNull
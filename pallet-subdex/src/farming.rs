@@ -0,0 +1,109 @@
+use super::*;
+use crate::math::{fixed_one, mul_div, to_balance, to_u256};
+use sp_core::U256;
+use sp_runtime::traits::{Saturating, UniqueSaturatedInto};
+
+/// Liquidity mining farm, set for exchange pool
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct Farm<T: Trait> {
+    // asset, rewards are paid in
+    pub reward_asset: Asset<T::AssetId>,
+    // rewards amount, distributed among all exchange pool shares per block
+    pub reward_rate: BalanceOf<T>,
+    // rewards accumulated per share since farm creation (fixed point)
+    pub reward_per_share: U256,
+    // last block, rewards were accumulated at
+    pub last_update_block: T::BlockNumber,
+}
+
+impl<T: Trait> Farm<T> {
+    pub fn new(reward_asset: Asset<T::AssetId>, now: T::BlockNumber) -> Self {
+        Self {
+            reward_asset,
+            reward_rate: BalanceOf::<T>::default(),
+            reward_per_share: U256::zero(),
+            last_update_block: now,
+        }
+    }
+
+    /// Accumulate rewards per share up to a given block
+    pub fn accumulate(
+        &mut self,
+        total_shares: BalanceOf<T>,
+        now: T::BlockNumber,
+    ) -> Result<(), Error<T>> {
+        if now <= self.last_update_block {
+            return Ok(());
+        }
+
+        if !total_shares.is_zero() && !self.reward_rate.is_zero() {
+            let blocks: u128 = (now - self.last_update_block).unique_saturated_into();
+
+            let rewards = to_u256::<T>(self.reward_rate)
+                .checked_mul(U256::from(blocks))
+                .ok_or(Error::<T>::OverflowOccured)?;
+
+            self.reward_per_share = self
+                .reward_per_share
+                .checked_add(mul_div::<T>(
+                    rewards,
+                    fixed_one(),
+                    to_u256::<T>(total_shares),
+                )?)
+                .ok_or(Error::<T>::OverflowOccured)?;
+        }
+
+        self.last_update_block = now;
+        Ok(())
+    }
+
+    // Rewards, accumulated by a given amount of shares since farm creation (rounded down)
+    fn accumulated_rewards(&self, shares: BalanceOf<T>) -> Result<BalanceOf<T>, Error<T>> {
+        to_balance::<T>(mul_div::<T>(
+            to_u256::<T>(shares),
+            self.reward_per_share,
+            fixed_one(),
+        )?)
+    }
+
+    /// Move pending rewards into unclaimed ones and reset reward debt, when position shares changed.
+    /// Farm rewards should be accumulated up to the current block first.
+    pub fn settle(
+        &self,
+        position: &mut FarmingPosition<T>,
+        shares_before: BalanceOf<T>,
+        shares_after: BalanceOf<T>,
+    ) -> Result<(), Error<T>> {
+        let pending_rewards = self
+            .accumulated_rewards(shares_before)?
+            .saturating_sub(position.reward_debt);
+
+        position.unclaimed_rewards = position
+            .unclaimed_rewards
+            .checked_add(&pending_rewards)
+            .ok_or(Error::<T>::OverflowOccured)?;
+
+        position.reward_debt = self.accumulated_rewards(shares_after)?;
+        Ok(())
+    }
+}
+
+/// Liquidity provider farming position
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct FarmingPosition<T: Trait> {
+    // rewards, accumulated by current position shares before they were provided
+    pub reward_debt: BalanceOf<T>,
+    // settled rewards, available to be claimed
+    pub unclaimed_rewards: BalanceOf<T>,
+}
+
+impl<T: Trait> Default for FarmingPosition<T> {
+    fn default() -> Self {
+        Self {
+            reward_debt: BalanceOf::<T>::default(),
+            unclaimed_rewards: BalanceOf::<T>::default(),
+        }
+    }
+}
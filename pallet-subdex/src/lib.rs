@@ -1,22 +1,41 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Codec, Decode, Encode};
-use frame_support::traits::Currency;
+use frame_support::traits::{Currency, ReservableCurrency};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
-    traits::{Get, WithdrawReason},
+    storage::IterableStorageDoubleMap,
+    traits::{EnsureOrigin, Get, WithdrawReason},
+    transactional,
+    weights::Weight,
     Parameter,
 };
 use frame_system::{self as system, ensure_signed};
-use sp_arithmetic::traits::{BaseArithmetic, Zero};
-use sp_runtime::traits::{
-    CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, MaybeSerializeDeserialize, Member,
+use math::{fixed_one, mul_div, mul_div_ceil, to_u256};
+use sp_arithmetic::traits::{BaseArithmetic, One, Zero};
+use sp_core::U256;
+use sp_runtime::{
+    traits::{AccountIdConversion, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member},
+    ModuleId, PerThing, Permill,
 };
 
-use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
+use sp_std::{cell::Cell, collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
 
+mod curve;
+mod dca;
 mod exchange;
-pub use exchange::Exchange;
+mod farming;
+mod limit_order;
+mod math;
+mod order_queue;
+mod stop_loss;
+pub use curve::{PoolCurve, WeightSchedule, MAX_AMPLIFICATION, MAX_TOTAL_WEIGHT};
+pub use dca::{DcaOrder, DcaOrderId};
+pub use exchange::{Exchange, FeeCharge, SwapDelta, SwapSimulation};
+pub use farming::{Farm, FarmingPosition};
+pub use limit_order::{LimitOrder, LimitOrderId, LIMIT_ORDER_FILL_SEARCH_STEPS};
+pub use order_queue::{ScheduledOrder, SWAP_COMPUTATION_WEIGHT, SWAP_READS, SWAP_WRITES};
+pub use stop_loss::{StopLossOrder, StopLossOrderId};
 
 #[cfg(feature = "std")]
 pub use serde::{Deserialize, Serialize};
@@ -27,7 +46,7 @@ pub type BalanceOf<T> =
 
 /// Enum, representing either main network currency, supported natively or our internal represenation for assets from other parachains
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Asset<AssetId: Default + Debug + Ord + Copy> {
     MainNetworkCurrency,
     ParachainAsset(AssetId),
@@ -39,6 +58,34 @@ impl<AssetId: Default + Debug + Ord + Copy> Default for Asset<AssetId> {
     }
 }
 
+/// Dex operation, performed as a part of operations batch
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub enum DexOperation<AccountId, AssetId: Default + Debug + Ord + Copy, Balance> {
+    /// first asset, first asset amount, second asset, second asset amount, pool curve
+    Initialize(Asset<AssetId>, Balance, Asset<AssetId>, Balance, PoolCurve),
+    /// asset in, asset in amount, asset out, min asset out amount, receiver, referrer
+    Swap(
+        Asset<AssetId>,
+        Balance,
+        Asset<AssetId>,
+        Balance,
+        AccountId,
+        Option<AccountId>,
+    ),
+    /// first asset, second asset, shares
+    Invest(Asset<AssetId>, Asset<AssetId>, Balance),
+    /// first asset, second asset, shares burned, min first asset received, min second asset received
+    Divest(Asset<AssetId>, Asset<AssetId>, Balance, Balance, Balance),
+}
+
+/// Weight of a single dex operation, performed as a part of operations batch
+pub const DEX_OPERATION_WEIGHT: Weight = 10_000;
+
+/// Exchanges, read during operations processing, written to the runtime storage once all operations performed
+pub type ExchangesCache<T> =
+    BTreeMap<(Asset<<T as Trait>::AssetId>, Asset<<T as Trait>::AssetId>), Exchange<T>>;
+
 /// Represents data, needed to charge treasury fee
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Default)]
@@ -63,11 +110,88 @@ impl<AccountId: Default + Debug, Balance: Default + Debug> DexTreasury<AccountId
     }
 }
 
+/// Dynamic joint fee rate parameters: fee rate scales linearly from min to max rate,
+/// as exchange volatility grows from zero to the volatility threshold
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DynamicFee {
+    // fee rate, applied when price is stable
+    pub min_fee_rate: Permill,
+    // fee rate, applied when volatility reaches the threshold
+    pub max_fee_rate: Permill,
+    // volatility, max fee rate is applied from
+    pub volatility_threshold: Permill,
+}
+
+impl DynamicFee {
+    pub fn new(
+        min_fee_rate: Permill,
+        max_fee_rate: Permill,
+        volatility_threshold: Permill,
+    ) -> Self {
+        DynamicFee {
+            min_fee_rate,
+            max_fee_rate,
+            volatility_threshold,
+        }
+    }
+
+    /// Ensure fee rates are ordered, max fee rate is below 100% and volatility threshold is greater than zero
+    pub fn ensure_valid<T: Trait>(&self) -> Result<(), Error<T>> {
+        ensure!(
+            self.min_fee_rate <= self.max_fee_rate
+                && self.max_fee_rate < Permill::from_percent(100)
+                && self.volatility_threshold.deconstruct() > 0,
+            Error::<T>::InvalidDynamicFee
+        );
+        Ok(())
+    }
+
+    /// Calculate fee rate for a given volatility
+    pub fn fee_rate(&self, volatility: Permill) -> Permill {
+        let volatility_threshold = self.volatility_threshold.deconstruct() as u64;
+        let fee_rate_range =
+            (self.max_fee_rate.deconstruct() - self.min_fee_rate.deconstruct()) as u64;
+        let fee_rate_increase = fee_rate_range
+            * (volatility.deconstruct() as u64).min(volatility_threshold)
+            / volatility_threshold;
+        Permill::from_parts(self.min_fee_rate.deconstruct() + fee_rate_increase as u32)
+    }
+}
+
+/// Used by runtime level borrowers (e.g. arbitrage or liquidation pallets) to handle flash swap output
+pub trait FlashSwapReceiver<T: Trait> {
+    /// Called right after `asset_out_amount` of `asset_out` credited to the `borrower` account.
+    /// At least `repayment_amount` of `asset_in` should be available on the `borrower` account, when this call completes,
+    /// otherwise the whole flash swap is rolled back.
+    fn on_flash_swap(
+        borrower: &T::AccountId,
+        asset_out: Asset<T::AssetId>,
+        asset_out_amount: BalanceOf<T>,
+        asset_in: Asset<T::AssetId>,
+        repayment_amount: BalanceOf<T>,
+        data: &[u8],
+    ) -> dispatch::DispatchResult;
+}
+
+impl<T: Trait> FlashSwapReceiver<T> for () {
+    fn on_flash_swap(
+        _borrower: &T::AccountId,
+        _asset_out: Asset<T::AssetId>,
+        _asset_out_amount: BalanceOf<T>,
+        _asset_in: Asset<T::AssetId>,
+        _repayment_amount: BalanceOf<T>,
+        _data: &[u8],
+    ) -> dispatch::DispatchResult {
+        Ok(())
+    }
+}
+
 pub trait Trait: system::Trait + pallet_timestamp::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
     /// Main network currency provider, used by subdex
-    type Currency: Currency<Self::AccountId>;
+    type Currency: ReservableCurrency<Self::AccountId>;
 
     // Used for cumulative price calculation
     type IMoment: From<<Self as pallet_timestamp::Trait>::Moment>
@@ -103,6 +227,33 @@ pub trait Trait: system::Trait + pallet_timestamp::Trait {
 
     /// Min parachain asset amount to perfrom invest/divest operations with.
     type MinParachainAssetAmount: Get<BalanceOf<Self>>;
+
+    /// Flash swap output handler, invoked before flash swap repayment.
+    type FlashSwapReceiver: FlashSwapReceiver<Self>;
+
+    /// Origin, allowed to set liquidity mining reward rates.
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Subdex module id, used to derive account, holding liquidity mining rewards.
+    type ModuleId: Get<ModuleId>;
+
+    /// Max pools ratio change, allowed per swap (can be overridden per exchange pool).
+    type MaxPriceImpact: Get<Permill>;
+
+    /// Min period, time weighted average prices are calculated over.
+    type TwapPeriod: Get<Self::IMoment>;
+
+    /// Max number of dex operations, performed as a part of a single operations batch.
+    type MaxBatchOperations: Get<u32>;
+
+    /// Max number of scheduled orders, processed per block (the rest are left in the queue for the next blocks).
+    type MaxScheduledOrdersPerBlock: Get<u32>;
+
+    /// Main network currency deposit, reserved per scheduled order and refunded, when order is closed.
+    type OrderDeposit: Get<BalanceOf<Self>>;
+
+    /// Max number of blocks, order with expiry can stay open for.
+    type MaxOrderLifetime: Get<Self::BlockNumber>;
 }
 
 decl_storage! {
@@ -110,12 +261,94 @@ decl_storage! {
         /// Maps both assets to their respective exchange pool
         pub Exchanges get(fn exchanges): double_map hasher(blake2_128_concat) Asset<T::AssetId>, hasher(blake2_128_concat) Asset<T::AssetId> => Exchange<T>;
 
+        /// Index of exchange pools per asset, maps each pool asset to the opposite one (in both directions)
+        pub PoolsByAsset: double_map hasher(blake2_128_concat) Asset<T::AssetId>, hasher(blake2_128_concat) Asset<T::AssetId> => ();
+
+        /// Total number of exchange pools
+        pub PoolCount get(fn pool_count): u32;
+
+        /// Index of exchange pools, given account holds shares of
+        pub AccountPools: double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) (Asset<T::AssetId>, Asset<T::AssetId>) => ();
+
         /// Balances of assets, located on other parachains.
-        pub AssetBalances get(fn asset_balances):
+        pub AssetBalances get(fn asset_balances) config():
+            double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AssetId => BalanceOf<T>;
+
+        /// Reserved balances of assets, located on other parachains (e.g. backing scheduled orders).
+        pub ReservedAssetBalances get(fn reserved_asset_balances):
             double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AssetId => BalanceOf<T>;
 
         /// Treasury data (used to charge fee, when enabled)
         pub DEXTreasury get(fn dex_treasury) config(): DexTreasury<T::AccountId, BalanceOf<T>>;
+
+        /// Max price impact overrides, set per exchange pool
+        pub PoolMaxPriceImpact get(fn pool_max_price_impact): double_map hasher(blake2_128_concat) Asset<T::AssetId>, hasher(blake2_128_concat) Asset<T::AssetId> => Option<Permill>;
+
+        /// Max deviation of post swap price from time weighted average price (guard is disabled, when not set)
+        pub MaxOracleDeviation get(fn max_oracle_deviation): Option<Permill>;
+
+        /// Dynamic joint fee rate parameters (fixed fee rate is used, when not set)
+        pub DynamicFeeParams get(fn dynamic_fee): Option<DynamicFee>;
+
+        /// Share of joint fee, paid to swap referrer
+        pub ReferralShare get(fn referral_share): Permill;
+
+        /// Joint fee discounts (fee tiers), set per account. Full discount exempts account from joint fee.
+        pub FeeExemptions get(fn fee_exemptions): map hasher(blake2_128_concat) T::AccountId => Option<Permill>;
+
+        /// Identifier, assigned to the next dollar cost averaging order
+        pub NextDcaOrderId get(fn next_dca_order_id): DcaOrderId;
+
+        /// Active dollar cost averaging orders
+        pub DcaOrders get(fn dca_orders): map hasher(blake2_128_concat) DcaOrderId => Option<DcaOrder<T>>;
+
+        /// Identifier, assigned to the next limit order
+        pub NextLimitOrderId get(fn next_limit_order_id): LimitOrderId;
+
+        /// Open limit orders
+        pub LimitOrders get(fn limit_orders): map hasher(blake2_128_concat) LimitOrderId => Option<LimitOrder<T>>;
+
+        /// Identifier, assigned to the next stop-loss order
+        pub NextStopLossOrderId get(fn next_stop_loss_order_id): StopLossOrderId;
+
+        /// Open stop-loss orders
+        pub StopLossOrders get(fn stop_loss_orders): map hasher(blake2_128_concat) StopLossOrderId => Option<StopLossOrder<T>>;
+
+        /// Scheduled orders queue: orders, scheduled at given block, in order of scheduling
+        pub OrderQueue get(fn order_queue):
+            double_map hasher(blake2_128_concat) T::BlockNumber, hasher(blake2_128_concat) u32 => Option<ScheduledOrder>;
+
+        /// Number of orders, pushed to the scheduled orders queue at given block
+        pub OrderQueueLength get(fn order_queue_length): map hasher(blake2_128_concat) T::BlockNumber => u32;
+
+        /// Block and index of the next scheduled orders queue entry to be processed
+        pub OrderQueueHead get(fn order_queue_head): (T::BlockNumber, u32);
+
+        /// Total number of scheduled orders queue entries, left to be processed
+        pub QueuedOrdersCount get(fn queued_orders_count): u32;
+
+        /// Liquidity mining farms, set per exchange pool
+        pub Farms get(fn farms): double_map hasher(blake2_128_concat) Asset<T::AssetId>, hasher(blake2_128_concat) Asset<T::AssetId> => Option<Farm<T>>;
+
+        /// Liquidity providers farming positions, per exchange pool
+        pub FarmingPositions get(fn farming_positions):
+            double_map hasher(blake2_128_concat) (Asset<T::AssetId>, Asset<T::AssetId>), hasher(blake2_128_concat) T::AccountId => FarmingPosition<T>;
+    }
+    add_extra_genesis {
+        /// Initial exchange pools: first asset, first asset amount, second asset, second asset amount, owner (receives initial shares)
+        config(exchanges): Vec<(Asset<T::AssetId>, BalanceOf<T>, Asset<T::AssetId>, BalanceOf<T>, T::AccountId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (first_asset, first_asset_amount, second_asset, second_asset_amount, owner) in &config.exchanges {
+                <Module<T>>::initialize_genesis_exchange(
+                    *first_asset,
+                    *first_asset_amount,
+                    *second_asset,
+                    *second_asset_amount,
+                    owner,
+                )
+                .expect("Invalid genesis exchange");
+            }
+        });
     }
 }
 
@@ -123,16 +356,105 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
+        BlockNumber = <T as system::Trait>::BlockNumber,
         Asset = Asset<<T as Trait>::AssetId>,
         Shares = BalanceOf<T>,
         Balance = BalanceOf<T>,
         TreasuryFee = Option<BalanceOf<T>>,
+        ReferralFee = Option<(<T as system::Trait>::AccountId, BalanceOf<T>)>,
     {
-        // account id, asset in, asset in amount, asset out, asset out amount, treasury fee
-        Exchanged(AccountId, Asset, Balance, Asset, Balance, TreasuryFee),
-        Invested(AccountId, Asset, Asset, Shares),
+        // sender account id, receiver account id, asset in, asset in amount, asset out, asset out amount, joint fee, treasury fee,
+        // referrer account id and referral fee
+        Exchanged(
+            AccountId,
+            AccountId,
+            Asset,
+            Balance,
+            Asset,
+            Balance,
+            Balance,
+            TreasuryFee,
+            ReferralFee,
+        ),
+        // borrower account id, asset in, asset in amount (repaid), asset out, asset out amount (borrowed), treasury fee
+        FlashSwapped(AccountId, Asset, Balance, Asset, Balance, TreasuryFee),
+        // investor account id, first asset, second asset, shares, first asset amount, second asset amount
+        Invested(AccountId, Asset, Asset, Shares, Balance, Balance),
         Initialized(AccountId, Asset, Asset, Shares),
-        Divested(AccountId, Asset, Asset, Shares),
+        // divestor account id, first asset, second asset, shares, first asset amount, second asset amount
+        Divested(AccountId, Asset, Asset, Shares, Balance, Balance),
+        // first asset, second asset, first asset pool, second asset pool, total shares (after operation performed)
+        PoolUpdated(Asset, Asset, Balance, Balance, Shares),
+        // first asset, second asset, reward asset, reward rate (per block)
+        RewardRateSet(Asset, Asset, Asset, Balance),
+        // funder account id, reward asset, amount
+        RewardsFunded(AccountId, Asset, Balance),
+        // account id, first asset, second asset, reward asset, amount
+        RewardsClaimed(AccountId, Asset, Asset, Asset, Balance),
+        // first asset, second asset, max price impact override (None, if global limit is used)
+        MaxPriceImpactSet(Asset, Asset, Option<Permill>),
+        // max deviation from time weighted average price (None, if guard is disabled)
+        MaxOracleDeviationSet(Option<Permill>),
+        // dynamic fee parameters (None, if fixed fee rate is used)
+        DynamicFeeSet(Option<DynamicFee>),
+        // share of joint fee, paid to swap referrer
+        ReferralShareSet(Permill),
+        // account id, joint fee discount (None, if account pays full joint fee)
+        FeeExemptionSet(AccountId, Option<Permill>),
+        // order id, owner account id, asset in, asset out, amount per period, period blocks, periods
+        DcaOrderCreated(
+            DcaOrderId,
+            AccountId,
+            Asset,
+            Asset,
+            Balance,
+            BlockNumber,
+            u32,
+        ),
+        // order id, asset in amount, asset out amount
+        DcaOrderExecuted(DcaOrderId, Balance, Balance),
+        // order id, asset in amount (refunded to the owner, when swap can not be performed)
+        DcaOrderPeriodSkipped(DcaOrderId, Balance),
+        // order id
+        DcaOrderCompleted(DcaOrderId),
+        // order id, remaining asset in amount (refunded to the owner)
+        DcaOrderCancelled(DcaOrderId, Balance),
+        // order id, owner account id, asset in, asset out, amount, min price (fixed point), expiry block
+        LimitOrderCreated(
+            LimitOrderId,
+            AccountId,
+            Asset,
+            Asset,
+            Balance,
+            U256,
+            BlockNumber,
+        ),
+        // order id, asset in amount filled, asset out amount
+        LimitOrderFilled(LimitOrderId, Balance, Balance),
+        // order id
+        LimitOrderCompleted(LimitOrderId),
+        // order id, remaining asset in amount (refunded to the owner)
+        LimitOrderCancelled(LimitOrderId, Balance),
+        // order id, remaining asset in amount (refunded to the owner)
+        LimitOrderExpired(LimitOrderId, Balance),
+        // order id, owner account id, asset in, asset out, amount, trigger price (fixed point), expiry block
+        StopLossOrderCreated(
+            StopLossOrderId,
+            AccountId,
+            Asset,
+            Asset,
+            Balance,
+            U256,
+            BlockNumber,
+        ),
+        // order id, time weighted average price (fixed point), asset in amount, asset out amount
+        StopLossOrderTriggered(StopLossOrderId, U256, Balance, Balance),
+        // order id, asset in amount (refunded to the owner, when triggered swap can not be performed)
+        StopLossOrderFailed(StopLossOrderId, Balance),
+        // order id, asset in amount (refunded to the owner)
+        StopLossOrderCancelled(StopLossOrderId, Balance),
+        // order id, asset in amount (refunded to the owner)
+        StopLossOrderExpired(StopLossOrderId, Balance),
     }
 );
 
@@ -189,6 +511,91 @@ decl_error! {
         /// Amount of parachain asset provided is below minimum
         ParachainAssetAmountBelowMin,
 
+        /// Flash swap repayment amount is above expectation
+        RepaymentAmountAboveExpectation,
+
+        /// Pool invariant plus fee is not restored after flash swap repayment
+        InvariantNotRestored,
+
+        /// Flash swapped exchange was changed by flash swap receiver, before flash swap repaid
+        FlashSwapReentrancy,
+
+        /// Number of operations in batch exceeds max allowed
+        TooManyBatchOperations,
+
+        /// Amplification coefficient should be greater than zero and not exceed max allowed
+        InvalidAmplificationCoefficient,
+
+        /// Curve invariant or pool calculation has not converged
+        CurveCalculationNotConverged,
+
+        /// Weights should be greater than zero and their total should not exceed max allowed
+        InvalidWeights,
+
+        /// Weights schedule should end after it starts and should not have ended yet
+        InvalidWeightSchedule,
+
+        /// Liquidity mining farm is not set for given exchange
+        FarmNotExists,
+
+        /// Farm reward asset can not be changed
+        InvalidRewardAsset,
+
+        /// No rewards available to be claimed
+        NoRewardsToClaim,
+
+        /// Not enough funds in rewards account to pay rewards
+        InsufficientRewardFunds,
+
+        /// Swap changes pools ratio more than max price impact allowed
+        PriceImpactTooHigh,
+
+        /// Price after swap deviates from time weighted average price more than allowed
+        OracleDeviationTooHigh,
+
+        /// Dynamic fee rates should be ordered, max fee rate should be below 100% and volatility threshold should be greater than zero
+        InvalidDynamicFee,
+
+        /// Referral share together with treasury share should not exceed joint fee
+        InvalidReferralShare,
+
+        /// Swap sender can not refer itself
+        InvalidReferrer,
+
+        /// Dollar cost averaging order period blocks and periods number should be greater than zero,
+        /// all periods should fit into max order lifetime
+        InvalidDcaSchedule,
+
+        /// Given dollar cost averaging order does not exist
+        DcaOrderNotExists,
+
+        /// Dollar cost averaging order can only be cancelled by its owner
+        NotDcaOrderOwner,
+
+        /// Limit order min price should be greater than zero
+        InvalidLimitPrice,
+
+        /// Limit order expiry block should be in the future, within max order lifetime
+        InvalidLimitOrderExpiry,
+
+        /// Given limit order does not exist
+        LimitOrderNotExists,
+
+        /// Limit order can only be cancelled by its owner
+        NotLimitOrderOwner,
+
+        /// Stop-loss order trigger price should be greater than zero
+        InvalidTriggerPrice,
+
+        /// Stop-loss order expiry block should be in the future, within max order lifetime
+        InvalidStopLossOrderExpiry,
+
+        /// Given stop-loss order does not exist
+        StopLossOrderNotExists,
+
+        /// Stop-loss order can only be cancelled by its owner
+        NotStopLossOrderOwner,
+
         // Safe math
 
         OverflowOccured,
@@ -197,251 +604,1990 @@ decl_error! {
     }
 }
 
-decl_module! {
-    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Process due scheduled orders: dollar cost averaging swaps, limit orders matching against exchange pools
+        /// and stop-loss orders trigger checks.
+        // Scheduled orders are processed in on_initialize rather than in on_finalize or an off-chain worker:
+        // on_initialize weight is registered before any extrinsic is applied, so block weight limit accounts for it,
+        // on_finalize can not return its weight, and off-chain worker would make execution non-deterministic
+        // (orders would be processed only, when some node submits a transaction, in arbitrary order).
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::process_order_queue(now)
+        }
+
+        /// Initialize new exchange pool, using given curve to price swaps
+        #[weight = 10_000]
+        pub fn initialize_exchange(
+            origin,
+            first_asset: Asset<T::AssetId>,
+            first_asset_amount: BalanceOf<T>,
+            second_asset: Asset<T::AssetId>,
+            second_asset_amount: BalanceOf<T>,
+            curve: PoolCurve
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let mut exchanges_cache = ExchangesCache::<T>::new();
+
+            Self::do_initialize_exchange(&mut exchanges_cache, &sender, first_asset, first_asset_amount, second_asset, second_asset_amount, curve, None)?;
+
+            // Update runtime exchange storage state
+            Self::update_exchanges(exchanges_cache);
+            Ok(())
+        }
+
+        /// Initialize new liquidity bootstrapping exchange pool, which weights shift linearly according to a given schedule.
+        /// Exchange is converted into a regular one, once schedule ended.
+        #[weight = 10_000]
+        pub fn initialize_bootstrapping_exchange(
+            origin,
+            first_asset: Asset<T::AssetId>,
+            first_asset_amount: BalanceOf<T>,
+            second_asset: Asset<T::AssetId>,
+            second_asset_amount: BalanceOf<T>,
+            weight_schedule: WeightSchedule<T::BlockNumber>
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let mut exchanges_cache = ExchangesCache::<T>::new();
+
+            let curve = weight_schedule.curve_at(<system::Module<T>>::block_number());
+
+            Self::do_initialize_exchange(
+                &mut exchanges_cache,
+                &sender,
+                first_asset,
+                first_asset_amount,
+                second_asset,
+                second_asset_amount,
+                curve,
+                Some(weight_schedule)
+            )?;
+
+            // Update runtime exchange storage state
+            Self::update_exchanges(exchanges_cache);
+            Ok(())
+        }
+
+        /// Perform swap of some asset exact amount to another asset amount.
+        /// Referrer (if provided) receives referral share of the joint fee.
+        #[weight = 10_000]
+        pub fn swap_exact_to(
+            origin,
+            asset_in: Asset<T::AssetId>,
+            asset_in_amount: BalanceOf<T>,
+            asset_out: Asset<T::AssetId>,
+            min_asset_out_amount: BalanceOf<T>,
+            receiver: T::AccountId,
+            referrer: Option<T::AccountId>
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            Self::perform_swap(&sender, asset_in, asset_in_amount, asset_out, min_asset_out_amount, &receiver, referrer)?;
+            Ok(())
+        }
+
+        /// Borrow exact asset out amount from exchange pool and repay it with asset in amount (including fee) within the same call.
+        /// Runtime level `FlashSwapReceiver` is invoked after asset out amount credited to the borrower.
+        /// Exchange is updated only after repayment succeeded, so receiver can not operate on the flash swapped exchange.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn flash_swap(
+            origin,
+            asset_out: Asset<T::AssetId>,
+            asset_out_amount: BalanceOf<T>,
+            asset_in: Asset<T::AssetId>,
+            max_asset_in_amount: BalanceOf<T>,
+            data: Vec<u8>
+        ) -> dispatch::DispatchResult {
+            let borrower = ensure_signed(origin)?;
+
+            // Ensure assets are different
+            Self::ensure_valid_exchange(asset_in, asset_out)?;
+
+            let (adjusted_first_asset_id, adjusted_second_asset_id, adjsuted) = Self::adjust_assets_order(asset_in, asset_out);
+
+            // Ensure given exchange already exists
+            let mut exchange = Self::ensure_exchange_exists(adjusted_first_asset_id, adjusted_second_asset_id)?;
+
+            // Calculate repayment amount, swap delta after repayment performed and treasury fee (if enabled)
+            let (asset_in_amount, mut asset_swap_delta, treasury_fee_data) = if !adjsuted {
+                let asset_in_amount = exchange.calculate_first_asset_flash_swap_repayment(asset_out_amount, Some(&borrower))?;
+
+                let (first_to_second_asset_swap_delta, treasury_fee_data, _) =
+                    exchange.calculate_first_to_second_asset_swap(asset_in_amount, Some(&borrower), None)?;
+
+                (asset_in_amount, first_to_second_asset_swap_delta, treasury_fee_data)
+            } else {
+                let asset_in_amount = exchange.calculate_second_asset_flash_swap_repayment(asset_out_amount, Some(&borrower))?;
+
+                let (second_to_first_asset_swap_delta, treasury_fee_data, _) =
+                    exchange.calculate_second_to_first_asset_swap(asset_in_amount, Some(&borrower), None)?;
+
+                (asset_in_amount, second_to_first_asset_swap_delta, treasury_fee_data)
+            };
+
+            // Ensure repayment satisfies borrower expectations
+            ensure!(asset_in_amount <= max_asset_in_amount, Error::<T>::RepaymentAmountAboveExpectation);
+
+            // Ensure invariant plus fee is restored after repayment
+            ensure!(asset_swap_delta.amount >= asset_out_amount, Error::<T>::InvariantNotRestored);
+
+            // Only borrowed amount leaves the pool
+            if !adjsuted {
+                asset_swap_delta.second_asset_pool = exchange.second_asset_pool()
+                    .checked_sub(&asset_out_amount)
+                    .ok_or(Error::<T>::InsufficientPool)?;
+            } else {
+                asset_swap_delta.first_asset_pool = exchange.first_asset_pool()
+                    .checked_sub(&asset_out_amount)
+                    .ok_or(Error::<T>::InsufficientPool)?;
+            }
+
+            // Ensure swap does not move pools ratio beyond the limit
+            Self::ensure_price_impact(
+                &exchange,
+                adjusted_first_asset_id,
+                adjusted_second_asset_id,
+                &asset_swap_delta,
+                !adjsuted,
+            )?;
+
+            // Ensure flash swap does not move price too far from its recent average
+            Self::ensure_oracle_deviation(&exchange, &asset_swap_delta)?;
+
+            // Avoid overflow risks after flash swap performed
+            Self::ensure_can_hold_balance(&borrower, asset_out, asset_out_amount)?;
+
+            // Exchange state, flash swap was priced against
+            let exchange_before_flash_swap = exchange.clone();
+
+            // Update exchange pools
+            exchange.update_pools(asset_swap_delta.first_asset_pool, asset_swap_delta.second_asset_pool)?;
+
+            //
+            // == MUTATION SAFE (everything below is rolled back, if borrower fails to repay) ==
+            //
+
+            // Mint borrowed asset amount to the borrower
+            Self::mint_asset(&borrower, asset_out, asset_out_amount);
+
+            T::FlashSwapReceiver::on_flash_swap(&borrower, asset_out, asset_out_amount, asset_in, asset_in_amount, &data)?;
+
+            // Ensure receiver did not operate on the flash swapped exchange, as its update would be overwritten
+            ensure!(
+                Self::exchanges(adjusted_first_asset_id, adjusted_second_asset_id) == exchange_before_flash_swap,
+                Error::<T>::FlashSwapReentrancy
+            );
+
+            // Ensure borrower has sufficient balance to repay flash swap
+            Self::ensure_sufficient_balance(&borrower, asset_in, asset_in_amount)?;
+
+            // Slash repayment amount from borrower
+            Self::slash_asset(&borrower, asset_in, asset_in_amount);
+
+            // Update runtime exchange storage state only after flash swap repaid
+            Self::deposit_pool_updated_event(adjusted_first_asset_id, adjusted_second_asset_id, &exchange);
+
+            <Exchanges<T>>::insert(adjusted_first_asset_id, adjusted_second_asset_id, exchange);
+
+            // Charge treasury fee
+            let treasury_fee = if let Some((treasury_fee, dex_account_id)) = treasury_fee_data {
+                Self::mint_asset(&dex_account_id, asset_in, treasury_fee);
+                Some(treasury_fee)
+            } else {
+                None
+            };
+
+            Self::deposit_event(RawEvent::FlashSwapped(
+                borrower,
+                asset_in,
+                asset_in_amount,
+                asset_out,
+                asset_out_amount,
+                treasury_fee
+            ));
+            Ok(())
+        }
+
+        /// Used to invest liquidity into exchange pool
+        #[weight = 10_000]
+        pub fn invest_liquidity(origin, first_asset: Asset<T::AssetId>, second_asset: Asset<T::AssetId>, shares: BalanceOf<T>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let mut exchanges_cache = ExchangesCache::<T>::new();
+
+            Self::do_invest_liquidity(&mut exchanges_cache, &sender, first_asset, second_asset, shares)?;
+
+            // Update runtime exchange storage state
+            Self::update_exchanges(exchanges_cache);
+            Ok(())
+        }
+
+        /// Used to divest liquidity from exchange pool
+        #[weight = 10_000]
+        pub fn divest_liquidity(
+            origin,
+            first_asset: Asset<T::AssetId>,
+            second_asset: Asset<T::AssetId>,
+            shares_burned:  BalanceOf<T>,
+            min_first_asset_received: BalanceOf<T>,
+            min_second_asset_received: BalanceOf<T>
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let mut exchanges_cache = ExchangesCache::<T>::new();
+
+            Self::do_divest_liquidity(
+                &mut exchanges_cache,
+                &sender,
+                first_asset,
+                second_asset,
+                shares_burned,
+                min_first_asset_received,
+                min_second_asset_received
+            )?;
+
+            // Update runtime exchange storage state
+            Self::update_exchanges(exchanges_cache);
+            Ok(())
+        }
+
+        /// Set liquidity mining reward rate (per block) for given exchange pool.
+        /// Reward asset is fixed once farm is created, zero rate pauses rewards distribution.
+        #[weight = 10_000]
+        pub fn set_reward_rate(
+            origin,
+            first_asset: Asset<T::AssetId>,
+            second_asset: Asset<T::AssetId>,
+            reward_asset: Asset<T::AssetId>,
+            reward_rate: BalanceOf<T>
+        ) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            // Ensure assets are different
+            Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+            let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+
+            // Ensure given exchange already exists
+            let exchange = Self::ensure_exchange_exists(first_asset, second_asset)?;
+
+            let now = <system::Module<T>>::block_number();
+
+            // Accumulate rewards with the previous rate up to the current block
+            let mut farm = if let Some(mut farm) = Self::farms(first_asset, second_asset) {
+                ensure!(farm.reward_asset == reward_asset, Error::<T>::InvalidRewardAsset);
+                farm.accumulate(exchange.total_shares, now)?;
+                farm
+            } else {
+                Farm::new(reward_asset, now)
+            };
+
+            farm.reward_rate = reward_rate;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            <Farms<T>>::insert(first_asset, second_asset, farm);
+
+            Self::deposit_event(RawEvent::RewardRateSet(first_asset, second_asset, reward_asset, reward_rate));
+            Ok(())
+        }
+
+        /// Override max price impact for given exchange pool, None resets it to the global limit
+        #[weight = 10_000]
+        pub fn set_max_price_impact(
+            origin,
+            first_asset: Asset<T::AssetId>,
+            second_asset: Asset<T::AssetId>,
+            max_price_impact: Option<Permill>
+        ) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            // Ensure assets are different
+            Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+            let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+
+            // Ensure given exchange already exists
+            Self::ensure_exchange_exists(first_asset, second_asset)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            <PoolMaxPriceImpact<T>>::mutate(first_asset, second_asset, |pool_max_price_impact| *pool_max_price_impact = max_price_impact);
+
+            Self::deposit_event(RawEvent::MaxPriceImpactSet(first_asset, second_asset, max_price_impact));
+            Ok(())
+        }
+
+        /// Set max deviation of post swap price from time weighted average price, None disables the guard
+        #[weight = 10_000]
+        pub fn set_max_oracle_deviation(origin, max_oracle_deviation: Option<Permill>) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            MaxOracleDeviation::set(max_oracle_deviation);
+
+            Self::deposit_event(RawEvent::MaxOracleDeviationSet(max_oracle_deviation));
+            Ok(())
+        }
+
+        /// Set dynamic joint fee rate parameters, None switches back to the fixed fee rate
+        #[weight = 10_000]
+        pub fn set_dynamic_fee(origin, dynamic_fee: Option<DynamicFee>) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            // Ensure dynamic fee parameters are valid
+            if let Some(dynamic_fee) = dynamic_fee {
+                dynamic_fee.ensure_valid::<T>()?;
+            }
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            DynamicFeeParams::set(dynamic_fee);
+
+            Self::deposit_event(RawEvent::DynamicFeeSet(dynamic_fee));
+            Ok(())
+        }
+
+        /// Set share of joint fee, paid to swap referrer
+        #[weight = 10_000]
+        pub fn set_referral_share(origin, referral_share: Permill) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            // Ensure treasury fee and referral fee can always be taken from joint fee together
+            Self::ensure_valid_referral_share(referral_share)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            ReferralShare::put(referral_share);
+
+            Self::deposit_event(RawEvent::ReferralShareSet(referral_share));
+            Ok(())
+        }
+
+        /// Set joint fee discount for given account, None resets it to the full joint fee
+        #[weight = 10_000]
+        pub fn set_fee_exemption(origin, who: T::AccountId, fee_discount: Option<Permill>) -> dispatch::DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            <FeeExemptions<T>>::mutate(&who, |account_fee_discount| *account_fee_discount = fee_discount);
+
+            Self::deposit_event(RawEvent::FeeExemptionSet(who, fee_discount));
+            Ok(())
+        }
+
+        /// Transfer given asset amount to rewards account, used to pay liquidity mining rewards
+        #[weight = 10_000]
+        pub fn fund_rewards(origin, reward_asset: Asset<T::AssetId>, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let rewards_account_id = Self::account_id();
+
+            // Ensure account has sufficient balance to fund rewards
+            Self::ensure_sufficient_balance(&sender, reward_asset, amount)?;
+
+            // Avoid overflow risks
+            Self::ensure_can_hold_balance(&rewards_account_id, reward_asset, amount)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            Self::slash_asset(&sender, reward_asset, amount);
+
+            Self::mint_asset(&rewards_account_id, reward_asset, amount);
+
+            Self::deposit_event(RawEvent::RewardsFunded(sender, reward_asset, amount));
+            Ok(())
+        }
+
+        /// Claim liquidity mining rewards, accumulated by owned exchange pool shares
+        #[weight = 10_000]
+        pub fn claim_rewards(origin, first_asset: Asset<T::AssetId>, second_asset: Asset<T::AssetId>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            // Ensure assets are different
+            Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+            let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+
+            let exchange = Self::exchanges(first_asset, second_asset);
+            let shares = exchange.shares(&sender);
+
+            // Settle pending rewards, keeping shares unchanged
+            let (farm, mut position) = Self::calculate_farming_update(
+                first_asset,
+                second_asset,
+                &sender,
+                exchange.total_shares,
+                shares,
+                shares
+            )?
+            .ok_or(Error::<T>::FarmNotExists)?;
+
+            let rewards = position.unclaimed_rewards;
+            ensure!(!rewards.is_zero(), Error::<T>::NoRewardsToClaim);
+
+            let rewards_account_id = Self::account_id();
+
+            // Ensure rewards account has sufficient balance to pay rewards
+            Self::ensure_sufficient_balance(&rewards_account_id, farm.reward_asset, rewards)
+                .map_err(|_| Error::<T>::InsufficientRewardFunds)?;
+
+            // Avoid overflow risks
+            Self::ensure_can_hold_balance(&sender, farm.reward_asset, rewards)?;
+
+            position.unclaimed_rewards = BalanceOf::<T>::zero();
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            Self::slash_asset(&rewards_account_id, farm.reward_asset, rewards);
+
+            Self::mint_asset(&sender, farm.reward_asset, rewards);
+
+            let reward_asset = farm.reward_asset;
+
+            Self::update_farming(first_asset, second_asset, &sender, Some((farm, position)));
+
+            Self::deposit_event(RawEvent::RewardsClaimed(sender, first_asset, second_asset, reward_asset, rewards));
+            Ok(())
+        }
+
+        /// Perform a batch of dex operations atomically.
+        /// Operations are performed in order against cached exchanges state, each touched exchange is written to the runtime storage once.
+        #[weight = DEX_OPERATION_WEIGHT.saturating_mul(operations.len() as Weight)]
+        #[transactional]
+        pub fn batch_operations(
+            origin,
+            operations: Vec<DexOperation<T::AccountId, T::AssetId, BalanceOf<T>>>
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(operations.len() <= T::MaxBatchOperations::get() as usize, Error::<T>::TooManyBatchOperations);
+
+            let mut exchanges_cache = ExchangesCache::<T>::new();
+
+            for operation in operations {
+                match operation {
+                    DexOperation::Initialize(first_asset, first_asset_amount, second_asset, second_asset_amount, curve) => {
+                        Self::do_initialize_exchange(&mut exchanges_cache, &sender, first_asset, first_asset_amount, second_asset, second_asset_amount, curve, None)?;
+                    }
+                    DexOperation::Swap(asset_in, asset_in_amount, asset_out, min_asset_out_amount, receiver, referrer) => {
+                        Self::do_swap(&mut exchanges_cache, &sender, asset_in, asset_in_amount, asset_out, min_asset_out_amount, &receiver, referrer)?;
+                    }
+                    DexOperation::Invest(first_asset, second_asset, shares) => {
+                        Self::do_invest_liquidity(&mut exchanges_cache, &sender, first_asset, second_asset, shares)?;
+                    }
+                    DexOperation::Divest(first_asset, second_asset, shares_burned, min_first_asset_received, min_second_asset_received) => {
+                        Self::do_divest_liquidity(
+                            &mut exchanges_cache,
+                            &sender,
+                            first_asset,
+                            second_asset,
+                            shares_burned,
+                            min_first_asset_received,
+                            min_second_asset_received
+                        )?;
+                    }
+                }
+            }
+
+            // Update runtime exchange storage state
+            Self::update_exchanges(exchanges_cache);
+            Ok(())
+        }
+
+        /// Create dollar cost averaging order: swap asset in amount per period to asset out every period blocks, given number of periods.
+        /// Asset in amount for all periods is reserved up front together with order deposit, the first swap is performed at the next block.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn create_dca_order(
+            origin,
+            asset_in: Asset<T::AssetId>,
+            asset_out: Asset<T::AssetId>,
+            amount_per_period: BalanceOf<T>,
+            period_blocks: T::BlockNumber,
+            periods: u32,
+            min_out_per_period: BalanceOf<T>
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(!period_blocks.is_zero() && periods > 0, Error::<T>::InvalidDcaSchedule);
+
+            // Blocks, all periods span, should not exceed max order lifetime
+            let schedule_blocks = period_blocks
+                .checked_mul(&periods.into())
+                .filter(|schedule_blocks| *schedule_blocks <= T::MaxOrderLifetime::get());
+
+            ensure!(schedule_blocks.is_some(), Error::<T>::InvalidDcaSchedule);
+
+            let order = DcaOrder {
+                owner: sender.clone(),
+                asset_in,
+                asset_out,
+                amount_per_period,
+                period_blocks,
+                periods_left: periods,
+                min_out_per_period,
+                next_execution: <system::Module<T>>::block_number() + One::one(),
+                deposit: T::OrderDeposit::get(),
+            };
+
+            let reserved_amount = order.remaining_amount()?;
+
+            // Ensure order can be placed and account has sufficient balance to reserve funds for all periods together with order deposit
+            Self::ensure_can_place_order(&sender, asset_in, amount_per_period, asset_out, reserved_amount)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            Self::reserve_order_funds(&sender, asset_in, reserved_amount, order.deposit)?;
+
+            let order_id = Self::next_dca_order_id();
+
+            NextDcaOrderId::put(order_id + 1);
+
+            Self::schedule_order(order.next_execution, ScheduledOrder::Dca(order_id));
+
+            <DcaOrders<T>>::insert(order_id, order);
+
+            Self::deposit_event(RawEvent::DcaOrderCreated(order_id, sender, asset_in, asset_out, amount_per_period, period_blocks, periods));
+            Ok(())
+        }
+
+        /// Cancel dollar cost averaging order, refunding remaining asset in amount to the owner
+        #[weight = 10_000]
+        pub fn cancel_dca_order(origin, order_id: DcaOrderId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let order = Self::dca_orders(order_id).ok_or(Error::<T>::DcaOrderNotExists)?;
+
+            ensure!(order.owner == sender, Error::<T>::NotDcaOrderOwner);
+
+            let remaining_amount = order.remaining_amount()?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            <DcaOrders<T>>::remove(order_id);
+
+            Self::release_order_funds(&sender, order.asset_in, remaining_amount, order.deposit);
+
+            Self::deposit_event(RawEvent::DcaOrderCancelled(order_id, remaining_amount));
+            Ok(())
+        }
+
+        /// Create limit order: swap asset in amount to asset out, whenever it can be filled at or above min price
+        /// (asset out amount per asset in amount, fixed point with 10^18 precision). Order can be filled partially
+        /// and is checked against exchange pool in the scheduled orders queue (starting from the next block) till expiry block.
+        /// Asset in amount is reserved up front together with order deposit.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn create_limit_order(
+            origin,
+            asset_in: Asset<T::AssetId>,
+            asset_in_amount: BalanceOf<T>,
+            asset_out: Asset<T::AssetId>,
+            min_price: U256,
+            expiry: T::BlockNumber
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(!min_price.is_zero(), Error::<T>::InvalidLimitPrice);
+
+            let next_check = <system::Module<T>>::block_number() + One::one();
+
+            ensure!(Self::is_valid_order_expiry(next_check, expiry), Error::<T>::InvalidLimitOrderExpiry);
+
+            // Ensure order can be placed and account has sufficient balance to reserve order amount together with order deposit
+            Self::ensure_can_place_order(&sender, asset_in, asset_in_amount, asset_out, asset_in_amount)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            let deposit = T::OrderDeposit::get();
+
+            Self::reserve_order_funds(&sender, asset_in, asset_in_amount, deposit)?;
+
+            let order_id = Self::next_limit_order_id();
+
+            NextLimitOrderId::put(order_id + 1);
+
+            Self::schedule_order(next_check, ScheduledOrder::Limit(order_id));
+
+            <LimitOrders<T>>::insert(order_id, LimitOrder {
+                owner: sender.clone(),
+                asset_in,
+                asset_out,
+                amount_left: asset_in_amount,
+                min_price,
+                expiry,
+                next_check,
+                deposit,
+            });
+
+            Self::deposit_event(RawEvent::LimitOrderCreated(order_id, sender, asset_in, asset_out, asset_in_amount, min_price, expiry));
+            Ok(())
+        }
+
+        /// Cancel limit order, refunding asset in amount, left to be filled, together with order deposit to the owner
+        #[weight = 10_000]
+        pub fn cancel_limit_order(origin, order_id: LimitOrderId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let order = Self::limit_orders(order_id).ok_or(Error::<T>::LimitOrderNotExists)?;
+
+            ensure!(order.owner == sender, Error::<T>::NotLimitOrderOwner);
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            Self::close_limit_order(order_id, &order);
+
+            Self::deposit_event(RawEvent::LimitOrderCancelled(order_id, order.amount_left));
+            Ok(())
+        }
+
+        /// Create stop-loss order: swap asset in amount to asset out, once time weighted average price of asset in
+        /// (asset out amount per asset in amount, fixed point with 10^18 precision) falls to or below trigger price.
+        /// Order is checked in the scheduled orders queue (starting from the next block) till expiry block.
+        /// Asset in amount is reserved up front together with order deposit.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn create_stop_loss_order(
+            origin,
+            asset_in: Asset<T::AssetId>,
+            asset_in_amount: BalanceOf<T>,
+            asset_out: Asset<T::AssetId>,
+            trigger_price: U256,
+            min_asset_out_amount: BalanceOf<T>,
+            expiry: T::BlockNumber
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(!trigger_price.is_zero(), Error::<T>::InvalidTriggerPrice);
+
+            let next_check = <system::Module<T>>::block_number() + One::one();
+
+            ensure!(Self::is_valid_order_expiry(next_check, expiry), Error::<T>::InvalidStopLossOrderExpiry);
+
+            // Ensure order can be placed and account has sufficient balance to reserve order amount together with order deposit
+            Self::ensure_can_place_order(&sender, asset_in, asset_in_amount, asset_out, asset_in_amount)?;
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            let deposit = T::OrderDeposit::get();
+
+            Self::reserve_order_funds(&sender, asset_in, asset_in_amount, deposit)?;
+
+            let order_id = Self::next_stop_loss_order_id();
+
+            NextStopLossOrderId::put(order_id + 1);
+
+            Self::schedule_order(next_check, ScheduledOrder::StopLoss(order_id));
+
+            <StopLossOrders<T>>::insert(order_id, StopLossOrder {
+                owner: sender.clone(),
+                asset_in,
+                asset_out,
+                amount: asset_in_amount,
+                trigger_price,
+                min_out: min_asset_out_amount,
+                expiry,
+                next_check,
+                deposit,
+            });
+
+            Self::deposit_event(RawEvent::StopLossOrderCreated(order_id, sender, asset_in, asset_out, asset_in_amount, trigger_price, expiry));
+            Ok(())
+        }
+
+        /// Cancel stop-loss order, refunding reserved asset in amount together with order deposit to the owner
+        #[weight = 10_000]
+        pub fn cancel_stop_loss_order(origin, order_id: StopLossOrderId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let order = Self::stop_loss_orders(order_id).ok_or(Error::<T>::StopLossOrderNotExists)?;
+
+            ensure!(order.owner == sender, Error::<T>::NotStopLossOrderOwner);
+
+            //
+            // == MUTATION SAFE ==
+            //
+
+            Self::close_stop_loss_order(order_id, &order);
+
+            Self::deposit_event(RawEvent::StopLossOrderCancelled(order_id, order.amount));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Ensure exchange assets are different
+    pub fn ensure_valid_exchange(
+        asset_in: Asset<T::AssetId>,
+        asset_out: Asset<T::AssetId>,
+    ) -> Result<(), Error<T>> {
+        match (asset_in, asset_out) {
+            (Asset::MainNetworkCurrency, Asset::MainNetworkCurrency) => {
+                Err(Error::<T>::InvalidExchange)
+            }
+            (Asset::ParachainAsset(asset_in_id), Asset::ParachainAsset(asset_out_id))
+                if asset_in_id == asset_out_id =>
+            {
+                Err(Error::<T>::InvalidExchange)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Dry run swap of some asset exact amount to another asset, without any state changes.
+    /// Returns swap delta together with fee breakdown (for a swap without referrer), price impact and effective price.
+    /// Fee discount of a given sender (if provided) is taken into account.
+    pub fn simulate_swap(
+        asset_in: Asset<T::AssetId>,
+        asset_in_amount: BalanceOf<T>,
+        asset_out: Asset<T::AssetId>,
+        sender: Option<T::AccountId>,
+    ) -> Result<SwapSimulation<T>, dispatch::DispatchError> {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(asset_in, asset_out)?;
+
+        let (first_asset, second_asset, adjusted) = Self::adjust_assets_order(asset_in, asset_out);
+
+        let exchange = Self::exchanges(first_asset, second_asset);
+
+        // Ensure given exchange already exists
+        exchange.ensure_exists()?;
+
+        let (swap_delta, treasury_fee_data) = if !adjusted {
+            let (swap_delta, treasury_fee_data, _) = exchange
+                .calculate_first_to_second_asset_swap(asset_in_amount, sender.as_ref(), None)?;
+
+            // Ensure second asset amount is available for withdraw
+            exchange.ensure_second_asset_amount(swap_delta.amount, BalanceOf::<T>::zero())?;
+
+            (swap_delta, treasury_fee_data)
+        } else {
+            let (swap_delta, treasury_fee_data, _) = exchange
+                .calculate_second_to_first_asset_swap(asset_in_amount, sender.as_ref(), None)?;
+
+            // Ensure first asset amount is available for withdraw
+            exchange.ensure_first_asset_amount(swap_delta.amount, BalanceOf::<T>::zero())?;
+
+            (swap_delta, treasury_fee_data)
+        };
+
+        let treasury_fee = treasury_fee_data.map(|(treasury_fee, _)| treasury_fee);
+
+        let lp_fee = exchange
+            .calculate_joint_fee(asset_in_amount, sender.as_ref())?
+            .checked_sub(&treasury_fee.unwrap_or_default())
+            .ok_or(Error::<T>::UnderflowOccured)?;
+
+        let price_impact = exchange.calculate_price_impact(&swap_delta, !adjusted)?;
+
+        let effective_price = mul_div::<T>(
+            to_u256::<T>(swap_delta.amount),
+            fixed_one(),
+            to_u256::<T>(asset_in_amount),
+        )?;
+
+        Ok(SwapSimulation {
+            swap_delta,
+            lp_fee,
+            treasury_fee,
+            price_impact,
+            effective_price,
+        })
+    }
+
+    /// Perform swap of some asset exact amount to another asset amount, crediting receiver account.
+    /// All checks are performed before any mutation, so failed swap never leaves partially updated state.
+    /// Returns asset out amount.
+    pub fn perform_swap(
+        sender: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        asset_in_amount: BalanceOf<T>,
+        asset_out: Asset<T::AssetId>,
+        min_asset_out_amount: BalanceOf<T>,
+        receiver: &T::AccountId,
+        referrer: Option<T::AccountId>,
+    ) -> Result<BalanceOf<T>, dispatch::DispatchError> {
+        let mut exchanges_cache = ExchangesCache::<T>::new();
+
+        let asset_out_amount = Self::do_swap(
+            &mut exchanges_cache,
+            sender,
+            asset_in,
+            asset_in_amount,
+            asset_out,
+            min_asset_out_amount,
+            receiver,
+            referrer,
+        )?;
+
+        // Update runtime exchange storage state
+        Self::update_exchanges(exchanges_cache);
+        Ok(asset_out_amount)
+    }
+
+    /// Get exchange from the cache, falling back to the runtime storage
+    fn cached_exchange(
+        exchanges_cache: &ExchangesCache<T>,
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+    ) -> Exchange<T> {
+        exchanges_cache
+            .get(&(first_asset, second_asset))
+            .cloned()
+            .unwrap_or_else(|| Self::exchanges(first_asset, second_asset))
+    }
+
+    /// Write all cached exchanges to the runtime storage
+    fn update_exchanges(exchanges_cache: ExchangesCache<T>) {
+        for ((first_asset, second_asset), exchange) in exchanges_cache {
+            <Exchanges<T>>::insert(first_asset, second_asset, exchange);
+        }
+    }
+
+    /// List all exchange pools, containing given asset (with adjusted assets order)
+    pub fn pools_of(asset: Asset<T::AssetId>) -> Vec<(Asset<T::AssetId>, Asset<T::AssetId>)> {
+        <PoolsByAsset<T>>::iter_prefix(asset)
+            .map(|(opposite_asset, _)| {
+                let (first_asset, second_asset, _) =
+                    Self::adjust_assets_order(asset, opposite_asset);
+                (first_asset, second_asset)
+            })
+            .collect()
+    }
+
+    // Add newly initialized exchange pool to the pools index
+    fn index_pool(first_asset: Asset<T::AssetId>, second_asset: Asset<T::AssetId>) {
+        <PoolsByAsset<T>>::insert(first_asset, second_asset, ());
+        <PoolsByAsset<T>>::insert(second_asset, first_asset, ());
+        PoolCount::mutate(|pool_count| *pool_count = pool_count.saturating_add(1));
+    }
+
+    /// List all liquidity positions of given account:
+    /// exchange pool assets, shares owned and underlying assets amounts (rounded down)
+    pub fn positions_of(
+        who: &T::AccountId,
+    ) -> Vec<(
+        Asset<T::AssetId>,
+        Asset<T::AssetId>,
+        BalanceOf<T>,
+        BalanceOf<T>,
+        BalanceOf<T>,
+    )> {
+        <AccountPools<T>>::iter_prefix(who)
+            .filter_map(|((first_asset, second_asset), _)| {
+                let exchange = Self::exchanges(first_asset, second_asset);
+                let shares = exchange.shares(who);
+                let (first_asset_amount, second_asset_amount) =
+                    exchange.calculate_divest_amounts(shares).ok()?;
+                Some((
+                    first_asset,
+                    second_asset,
+                    shares,
+                    first_asset_amount,
+                    second_asset_amount,
+                ))
+            })
+            .collect()
+    }
+
+    // Emit exchange pool state after it was updated, so that indexers do not need to read storage
+    fn deposit_pool_updated_event(
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        exchange: &Exchange<T>,
+    ) {
+        Self::deposit_event(RawEvent::PoolUpdated(
+            first_asset,
+            second_asset,
+            exchange.first_asset_pool(),
+            exchange.second_asset_pool(),
+            exchange.total_shares,
+        ));
+    }
+
+    // Keep account pools index in sync with account shares, after they changed
+    fn index_account_pool(
+        who: &T::AccountId,
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        shares: BalanceOf<T>,
+    ) {
+        if shares.is_zero() {
+            <AccountPools<T>>::remove(who, (first_asset, second_asset));
+        } else {
+            <AccountPools<T>>::insert(who, (first_asset, second_asset), ());
+        }
+    }
+
+    /// Max price impact, allowed per swap for given exchange pool
+    pub fn max_price_impact(
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+    ) -> Permill {
+        Self::pool_max_price_impact(first_asset, second_asset)
+            .unwrap_or_else(T::MaxPriceImpact::get)
+    }
+
+    /// Ensure swap with given delta does not exceed max price impact, allowed for given exchange pool
+    pub fn ensure_price_impact(
+        exchange: &Exchange<T>,
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        swap_delta: &SwapDelta<T>,
+        first_to_second: bool,
+    ) -> dispatch::DispatchResult {
+        ensure!(
+            exchange.calculate_price_impact(swap_delta, first_to_second)?
+                <= Self::max_price_impact(first_asset, second_asset),
+            Error::<T>::PriceImpactTooHigh
+        );
+        Ok(())
+    }
+
+    /// Ensure price after swap with given delta does not deviate from time weighted average price
+    /// more than allowed. Passes, if guard is disabled or average price is not available yet.
+    pub fn ensure_oracle_deviation(
+        exchange: &Exchange<T>,
+        swap_delta: &SwapDelta<T>,
+    ) -> dispatch::DispatchResult {
+        if let Some(max_oracle_deviation) = Self::max_oracle_deviation() {
+            if let Some(oracle_deviation) = exchange.calculate_oracle_deviation(swap_delta)? {
+                ensure!(
+                    oracle_deviation <= max_oracle_deviation,
+                    Error::<T>::OracleDeviationTooHigh
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Ensure referral share together with treasury share (if enabled) does not exceed joint fee
+    pub fn ensure_valid_referral_share(referral_share: Permill) -> dispatch::DispatchResult {
+        let treasury_share = if let Ok(dex_treasury) = <DEXTreasury<T>>::try_get() {
+            // Treasury share is rounded up, so that both fees never exceed joint fee
+            mul_div_ceil::<T>(
+                to_u256::<T>(dex_treasury.treasury_fee_rate_nominator),
+                U256::from(Permill::ACCURACY),
+                to_u256::<T>(dex_treasury.treasury_fee_rate_denominator),
+            )?
+        } else {
+            U256::zero()
+        };
+
+        ensure!(
+            treasury_share + U256::from(referral_share.deconstruct())
+                <= U256::from(Permill::ACCURACY),
+            Error::<T>::InvalidReferralShare
+        );
+        Ok(())
+    }
+
+    /// Account, holding liquidity mining rewards
+    pub fn account_id() -> T::AccountId {
+        T::ModuleId::get().into_account()
+    }
+
+    // Process scheduled orders queue, starting from its head, till due orders are exhausted or per block cap is reached.
+    // Orders are processed in order of scheduling, so orders, left in the queue due to the cap, are processed first in the next blocks,
+    // while processed orders are rescheduled after them. Only the queue head is read, so block weight is bounded by the cap.
+    fn process_order_queue(now: T::BlockNumber) -> Weight {
+        let max_orders = T::MaxScheduledOrdersPerBlock::get();
+
+        let mut weight = T::DbWeight::get().reads_writes(2, 2);
+
+        // Skip blocks, no orders are scheduled at, when queue is empty
+        let (mut block, mut index) = if Self::queued_orders_count() == 0 {
+            <OrderQueueLength<T>>::remove(Self::order_queue_head().0);
+            (now, 0)
+        } else {
+            Self::order_queue_head()
+        };
+
+        // Both processed orders and visited queue blocks are capped, so that lagging queue head catches up gradually
+        let (mut processed_orders, mut visited_blocks) = (0, 0);
+
+        while block <= now && processed_orders < max_orders && visited_blocks <= max_orders {
+            weight = weight.saturating_add(T::DbWeight::get().reads(1));
+
+            if index < Self::order_queue_length(block) {
+                let scheduled_order = <OrderQueue<T>>::take(block, index);
+
+                QueuedOrdersCount::mutate(|count| *count = count.saturating_sub(1));
+
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(2, 2));
+
+                if let Some(scheduled_order) = scheduled_order {
+                    weight =
+                        weight.saturating_add(Self::process_scheduled_order(now, scheduled_order));
+                }
+
+                index += 1;
+                processed_orders += 1;
+            } else {
+                <OrderQueueLength<T>>::remove(block);
+
+                weight = weight.saturating_add(T::DbWeight::get().writes(1));
+
+                block += One::one();
+                index = 0;
+                visited_blocks += 1;
+            }
+        }
+
+        <OrderQueueHead<T>>::put((block, index));
+
+        weight
+    }
+
+    // Process a single scheduled order. Returns weight consumed.
+    fn process_scheduled_order(now: T::BlockNumber, scheduled_order: ScheduledOrder) -> Weight {
+        match scheduled_order {
+            ScheduledOrder::Dca(order_id) => Self::execute_dca_order(now, order_id),
+            ScheduledOrder::Limit(order_id) => Self::match_limit_order(now, order_id),
+            ScheduledOrder::StopLoss(order_id) => Self::check_stop_loss_order(now, order_id),
+        }
+    }
+
+    // Push order to the end of scheduled orders queue at a given block (entries are never removed before being processed)
+    fn schedule_order(block: T::BlockNumber, scheduled_order: ScheduledOrder) {
+        let index = Self::order_queue_length(block);
+
+        <OrderQueue<T>>::insert(block, index, scheduled_order);
+
+        <OrderQueueLength<T>>::insert(block, index + 1);
+
+        QueuedOrdersCount::mutate(|count| *count += 1);
+    }
+
+    // Weight of scheduling order (queue entry, queue length and queued orders count writes)
+    fn schedule_order_weight() -> Weight {
+        T::DbWeight::get().reads_writes(2, 3)
+    }
+
+    // Weight of a single swap through the regular swap path, performed by scheduled order
+    fn scheduled_swap_weight() -> Weight {
+        T::DbWeight::get()
+            .reads_writes(SWAP_READS, SWAP_WRITES)
+            .saturating_add(SWAP_COMPUTATION_WEIGHT)
+    }
+
+    // Weight of a single swap simulation, performed to check scheduled order
+    fn swap_simulation_weight() -> Weight {
+        T::DbWeight::get()
+            .reads(SWAP_READS)
+            .saturating_add(SWAP_COMPUTATION_WEIGHT)
+    }
+
+    // Check, whether order expiry block follows its first check block and lays within max order lifetime
+    fn is_valid_order_expiry(first_check: T::BlockNumber, expiry: T::BlockNumber) -> bool {
+        expiry > first_check
+            && expiry
+                <= <system::Module<T>>::block_number().saturating_add(T::MaxOrderLifetime::get())
+    }
+
+    // Ensure order can be placed on exchange of given assets and owner has sufficient balance
+    // to reserve order asset in amount together with order deposit (in main network currency)
+    fn ensure_can_place_order(
+        owner: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        asset_in_amount: BalanceOf<T>,
+        asset_out: Asset<T::AssetId>,
+        reserved_amount: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(asset_in, asset_out)?;
+
+        let (first_asset, second_asset, _) = Self::adjust_assets_order(asset_in, asset_out);
+
+        // Ensure given exchange already exists
+        Self::ensure_exchange_exists(first_asset, second_asset)?;
+
+        // Ensure min asset amount constraint satisfied
+        Self::ensure_min_asset_amount(asset_in, asset_in_amount)?;
+
+        let deposit = T::OrderDeposit::get();
+
+        if asset_in == Asset::MainNetworkCurrency {
+            let reserved_amount = reserved_amount
+                .checked_add(&deposit)
+                .ok_or(Error::<T>::OverflowOccured)?;
+
+            Self::ensure_can_reserve(owner, asset_in, reserved_amount)
+        } else {
+            Self::ensure_can_reserve(owner, asset_in, reserved_amount)?;
+            Self::ensure_can_reserve(owner, Asset::MainNetworkCurrency, deposit)
+        }
+    }
+
+    // Reserve order asset in amount together with order deposit (in main network currency) on owner account
+    fn reserve_order_funds(
+        owner: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        reserved_amount: BalanceOf<T>,
+        deposit: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        T::Currency::reserve(owner, deposit)?;
+        Self::reserve_asset(owner, asset_in, reserved_amount)
+    }
+
+    // Release order asset in amount, left reserved, together with order deposit back to owner account
+    fn release_order_funds(
+        owner: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        reserved_amount: BalanceOf<T>,
+        deposit: BalanceOf<T>,
+    ) {
+        Self::unreserve_asset(owner, asset_in, reserved_amount);
+        T::Currency::unreserve(owner, deposit);
+    }
+
+    // Swap reserved asset in amount of scheduled order owner through the regular swap path, crediting swap output to the owner.
+    // Amount is released from reserve only, when swap succeeds, as failed swap leaves no state changes.
+    #[transactional]
+    fn perform_scheduled_swap(
+        owner: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        asset_in_amount: BalanceOf<T>,
+        asset_out: Asset<T::AssetId>,
+        min_asset_out_amount: BalanceOf<T>,
+    ) -> Result<BalanceOf<T>, dispatch::DispatchError> {
+        Self::unreserve_asset(owner, asset_in, asset_in_amount);
+
+        Self::perform_swap(
+            owner,
+            asset_in,
+            asset_in_amount,
+            asset_out,
+            min_asset_out_amount,
+            owner,
+            None,
+        )
+    }
+
+    // Perform a single dollar cost averaging order period swap through the regular swap path.
+    // When swap can not be performed (e.g. min asset out amount is not satisfied), period amount is released to the owner.
+    // Returns weight consumed.
+    fn execute_dca_order(now: T::BlockNumber, order_id: DcaOrderId) -> Weight {
+        let mut order = if let Some(order) = Self::dca_orders(order_id) {
+            order
+        } else {
+            return 0;
+        };
+
+        match Self::perform_scheduled_swap(
+            &order.owner,
+            order.asset_in,
+            order.amount_per_period,
+            order.asset_out,
+            order.min_out_per_period,
+        ) {
+            Ok(asset_out_amount) => {
+                Self::deposit_event(RawEvent::DcaOrderExecuted(
+                    order_id,
+                    order.amount_per_period,
+                    asset_out_amount,
+                ));
+            }
+            Err(_) => {
+                Self::unreserve_asset(&order.owner, order.asset_in, order.amount_per_period);
+
+                Self::deposit_event(RawEvent::DcaOrderPeriodSkipped(
+                    order_id,
+                    order.amount_per_period,
+                ));
+            }
+        }
+
+        order.periods_left -= 1;
+
+        if order.periods_left == 0 {
+            <DcaOrders<T>>::remove(order_id);
+
+            T::Currency::unreserve(&order.owner, order.deposit);
+
+            Self::deposit_event(RawEvent::DcaOrderCompleted(order_id));
+        } else {
+            order.next_execution = now.saturating_add(order.period_blocks);
+
+            Self::schedule_order(order.next_execution, ScheduledOrder::Dca(order_id));
+
+            <DcaOrders<T>>::insert(order_id, order);
+        }
+
+        Self::scheduled_swap_weight()
+            .saturating_add(Self::schedule_order_weight())
+            .saturating_add(T::DbWeight::get().reads_writes(2, 2))
+    }
+
+    // Check a single limit order against exchange pool, filling it (possibly partially) through the regular swap path,
+    // when it can be filled at or above min price. Orders, which are not filled completely, are pushed to the end of the queue again.
+    // Expired orders are closed, remaining asset in amount and order deposit are refunded to the owner.
+    // Returns weight consumed, accounting for every swap simulation, performed to calculate order fill.
+    fn match_limit_order(now: T::BlockNumber, order_id: LimitOrderId) -> Weight {
+        let mut order = if let Some(order) = Self::limit_orders(order_id) {
+            order
+        } else {
+            return 0;
+        };
+
+        let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
+        if now >= order.expiry {
+            Self::close_limit_order(order_id, &order);
+            Self::deposit_event(RawEvent::LimitOrderExpired(order_id, order.amount_left));
+            return weight.saturating_add(T::DbWeight::get().writes(2));
+        }
+
+        let (fill_amount, simulations) = Self::calculate_limit_order_fill(&order);
+
+        weight = weight
+            .saturating_add(Self::swap_simulation_weight().saturating_mul(simulations as Weight));
+
+        if let Some(fill_amount) = fill_amount {
+            weight = weight.saturating_add(Self::scheduled_swap_weight());
+
+            // Failed swap leaves no state changes (order is checked again later, when it can not be filled at the moment)
+            let swap_result = order
+                .min_out_amount(fill_amount)
+                .map_err(Into::into)
+                .and_then(|min_out_amount| {
+                    Self::perform_scheduled_swap(
+                        &order.owner,
+                        order.asset_in,
+                        fill_amount,
+                        order.asset_out,
+                        min_out_amount,
+                    )
+                });
+
+            if let Ok(asset_out_amount) = swap_result {
+                order.amount_left -= fill_amount;
+
+                Self::deposit_event(RawEvent::LimitOrderFilled(
+                    order_id,
+                    fill_amount,
+                    asset_out_amount,
+                ));
+            }
+        }
+
+        if order.amount_left.is_zero() {
+            <LimitOrders<T>>::remove(order_id);
 
-        type Error = Error<T>;
+            T::Currency::unreserve(&order.owner, order.deposit);
 
-        fn deposit_event() = default;
+            Self::deposit_event(RawEvent::LimitOrderCompleted(order_id));
+        } else {
+            order.next_check = now + One::one();
 
-        /// Initialize new exchange pool
-        #[weight = 10_000]
-        pub fn initialize_exchange(
-            origin,
-            first_asset: Asset<T::AssetId>,
-            first_asset_amount: BalanceOf<T>,
-            second_asset: Asset<T::AssetId>,
-            second_asset_amount: BalanceOf<T>
-        ) -> dispatch::DispatchResult {
-            let sender = ensure_signed(origin)?;
+            Self::schedule_order(order.next_check, ScheduledOrder::Limit(order_id));
 
-            // Ensure assets are different
-            Self::ensure_valid_exchange(first_asset, second_asset)?;
+            <LimitOrders<T>>::insert(order_id, order);
+
+            weight = weight.saturating_add(Self::schedule_order_weight());
+        }
 
-            // Ensure min asset amounts constraint satisfied
-            Self::ensure_min_asset_amounts(first_asset, first_asset_amount, second_asset, second_asset_amount)?;
+        weight
+    }
 
-            // Adjust assets and their respective amount order
-            let (first_asset, first_asset_amount, second_asset, second_asset_amount) =
-                Self::adjust_assets_amount_order(first_asset, first_asset_amount, second_asset, second_asset_amount);
+    // Calculate max asset in amount, limit order can be filled with at or above its min price.
+    // Average swap price decreases with swap amount, so it is found by bisection. Amount, left to be filled,
+    // is kept above min asset amount, so that order remainder can always be filled later.
+    // Returns None, if order can not be filled at the moment, together with number of swap simulations performed.
+    fn calculate_limit_order_fill(order: &LimitOrder<T>) -> (Option<BalanceOf<T>>, u32) {
+        let simulations = Cell::new(0);
 
-            // Ensure given exchange pool does not exist yet
-            Self::ensure_exchange_not_exists(first_asset, second_asset)?;
+        let can_be_filled = |asset_in_amount: BalanceOf<T>| {
+            simulations.set(simulations.get() + 1);
 
-            // Ensure new liquidity pool can be launched successfully
-            Self::exchanges(first_asset, second_asset).ensure_launch()?;
+            Self::simulate_swap(
+                order.asset_in,
+                asset_in_amount,
+                order.asset_out,
+                Some(order.owner.clone()),
+            )
+            .ok()
+            .and_then(|swap_simulation| {
+                order
+                    .min_out_amount(asset_in_amount)
+                    .ok()
+                    .map(|min_out_amount| swap_simulation.swap_delta.amount >= min_out_amount)
+            })
+            .unwrap_or(false)
+        };
+
+        if can_be_filled(order.amount_left) {
+            return (Some(order.amount_left), simulations.get());
+        }
 
-            // Ensure account has sufficient balance to initialize exchange
-            Self::ensure_sufficient_balances(&sender, first_asset, first_asset_amount, second_asset, second_asset_amount)?;
+        let min_amount = Self::min_asset_amount(order.asset_in);
 
-            // Initialize new exchange pair
-            let (exchange, initial_shares) = Exchange::<T>::initialize_new(first_asset_amount, second_asset_amount, sender.clone())?;
+        let max_partial_amount = order.amount_left.saturating_sub(min_amount);
 
-            //
-            // == MUTATION SAFE ==
-            //
+        if max_partial_amount < min_amount || !can_be_filled(min_amount) {
+            return (None, simulations.get());
+        }
 
-            // Slash respective asset amounts from given account to complete initialize exchange operation
-            Self::slash_assets(&sender, first_asset, first_asset_amount, second_asset, second_asset_amount);
+        let two = BalanceOf::<T>::from(2u32);
 
-            Exchanges::<T>::insert(first_asset, second_asset, exchange);
+        // Max amount, order can be filled with, lays in [lower_bound, upper_bound) range
+        let (mut lower_bound, mut upper_bound) = (min_amount, max_partial_amount);
 
-            Self::deposit_event(RawEvent::Initialized(sender, first_asset, second_asset, initial_shares));
-            Ok(())
+        if can_be_filled(upper_bound) {
+            return (Some(upper_bound), simulations.get());
         }
 
-        /// Perform swap of some asset exact amount to another asset amount
-        #[weight = 10_000]
-        pub fn swap_exact_to(
-            origin,
-            asset_in: Asset<T::AssetId>,
-            asset_in_amount: BalanceOf<T>,
-            asset_out: Asset<T::AssetId>,
-            min_asset_out_amount: BalanceOf<T>,
-            receiver: T::AccountId
-        ) -> dispatch::DispatchResult {
-            let sender = ensure_signed(origin)?;
+        for _ in 0..LIMIT_ORDER_FILL_SEARCH_STEPS {
+            let middle = lower_bound + (upper_bound - lower_bound) / two;
+            if middle == lower_bound {
+                break;
+            }
+            if can_be_filled(middle) {
+                lower_bound = middle;
+            } else {
+                upper_bound = middle;
+            }
+        }
 
-            // Ensure assets are different
-            Self::ensure_valid_exchange(asset_in, asset_out)?;
+        (Some(lower_bound), simulations.get())
+    }
 
-            let (adjusted_first_asset_id, adjusted_second_asset_id, adjsuted) = Self::adjust_assets_order(asset_in, asset_out);
+    // Remove limit order, refunding asset in amount, left to be filled, together with order deposit to the owner
+    fn close_limit_order(order_id: LimitOrderId, order: &LimitOrder<T>) {
+        <LimitOrders<T>>::remove(order_id);
 
-            // Ensure given exchange already exists
-            let mut exchange = Self::ensure_exchange_exists(adjusted_first_asset_id, adjusted_second_asset_id)?;
+        Self::release_order_funds(
+            &order.owner,
+            order.asset_in,
+            order.amount_left,
+            order.deposit,
+        );
+    }
 
-            // Ensure account has sufficient balance to perform swap
-            Self::ensure_sufficient_balance(&sender, asset_in, asset_in_amount)?;
+    // Check a single stop-loss order trigger against exchange time weighted average price.
+    // Triggered order is swapped through the regular swap path and closed, reserved amount is refunded to the owner,
+    // when swap can not be performed. Orders, which are not triggered, are pushed to the end of the queue again.
+    // Expired orders are closed, reserved amount and order deposit are refunded to the owner. Returns weight consumed.
+    fn check_stop_loss_order(now: T::BlockNumber, order_id: StopLossOrderId) -> Weight {
+        let mut order = if let Some(order) = Self::stop_loss_orders(order_id) {
+            order
+        } else {
+            return 0;
+        };
+
+        let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
+        if now >= order.expiry {
+            Self::close_stop_loss_order(order_id, &order);
+            Self::deposit_event(RawEvent::StopLossOrderExpired(order_id, order.amount));
+            return weight.saturating_add(T::DbWeight::get().writes(2));
+        }
 
-            // Calculate swap delata and treasury fee (if enabled)
-            let (asset_swap_delta, treasury_fee_data) = if !adjsuted {
+        let (first_asset, second_asset, adjusted) =
+            Self::adjust_assets_order(order.asset_in, order.asset_out);
+
+        let twap_price = Self::exchanges(first_asset, second_asset)
+            .calculate_twap_price(!adjusted)
+            .ok()
+            .flatten()
+            .filter(|twap_price| *twap_price <= order.trigger_price);
+
+        weight = weight.saturating_add(T::DbWeight::get().reads(2));
+
+        if let Some(twap_price) = twap_price {
+            weight = weight.saturating_add(Self::scheduled_swap_weight());
+
+            // Failed swap leaves no state changes
+            match Self::perform_scheduled_swap(
+                &order.owner,
+                order.asset_in,
+                order.amount,
+                order.asset_out,
+                order.min_out,
+            ) {
+                Ok(asset_out_amount) => {
+                    <StopLossOrders<T>>::remove(order_id);
+
+                    T::Currency::unreserve(&order.owner, order.deposit);
+
+                    Self::deposit_event(RawEvent::StopLossOrderTriggered(
+                        order_id,
+                        twap_price,
+                        order.amount,
+                        asset_out_amount,
+                    ));
+                }
+                Err(_) => {
+                    Self::close_stop_loss_order(order_id, &order);
+
+                    Self::deposit_event(RawEvent::StopLossOrderFailed(order_id, order.amount));
+                }
+            }
+        } else {
+            order.next_check = now + One::one();
 
-                // Calculate first to second asset swap delta and treasury fee (if enabled)
-                let (first_to_second_asset_swap_delta, treasury_fee_data) =
-                    exchange.calculate_first_to_second_asset_swap(asset_in_amount)?;
+            Self::schedule_order(order.next_check, ScheduledOrder::StopLoss(order_id));
 
-                    // Ensure second asset amount is available for withdraw
-                    exchange.ensure_second_asset_amount(first_to_second_asset_swap_delta.amount, min_asset_out_amount)?;
+            <StopLossOrders<T>>::insert(order_id, order);
 
-                    // Avoid overflow risks after exchange operation performed
-                    Self::ensure_can_hold_balance(&sender, asset_out, first_to_second_asset_swap_delta.amount)?;
+            weight = weight.saturating_add(Self::schedule_order_weight());
+        }
 
-                    (first_to_second_asset_swap_delta, treasury_fee_data)
-            } else {
+        weight
+    }
 
-                // Calculate second to first asset swap delta and treasury fee (if enabled)
-                let (second_to_first_asset_swap_delta, treasury_fee_data) =
-                    exchange.calculate_second_to_first_asset_swap(asset_in_amount)?;
+    // Remove stop-loss order, refunding reserved asset in amount together with order deposit to the owner
+    fn close_stop_loss_order(order_id: StopLossOrderId, order: &StopLossOrder<T>) {
+        <StopLossOrders<T>>::remove(order_id);
 
-                    // Ensure first asset amount is available for withdraw
-                    exchange.ensure_first_asset_amount(second_to_first_asset_swap_delta.amount, min_asset_out_amount)?;
+        Self::release_order_funds(&order.owner, order.asset_in, order.amount, order.deposit);
+    }
 
-                    // Avoid overflow risks after exchange operation performed
-                    Self::ensure_can_hold_balance(&sender, asset_out, second_to_first_asset_swap_delta.amount)?;
+    // Calculate farm and farming position state after given account shares changed, settling pending rewards.
+    // Returns None, if farm is not set for given exchange.
+    fn calculate_farming_update(
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        who: &T::AccountId,
+        total_shares: BalanceOf<T>,
+        shares_before: BalanceOf<T>,
+        shares_after: BalanceOf<T>,
+    ) -> Result<Option<(Farm<T>, FarmingPosition<T>)>, Error<T>> {
+        if let Some(mut farm) = Self::farms(first_asset, second_asset) {
+            farm.accumulate(total_shares, <system::Module<T>>::block_number())?;
+
+            let mut position = Self::farming_positions((first_asset, second_asset), who);
+            farm.settle(&mut position, shares_before, shares_after)?;
+
+            Ok(Some((farm, position)))
+        } else {
+            Ok(None)
+        }
+    }
 
-                    (second_to_first_asset_swap_delta, treasury_fee_data)
-            };
+    // Write farm and farming position state to the runtime storage
+    fn update_farming(
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        who: &T::AccountId,
+        farming_update: Option<(Farm<T>, FarmingPosition<T>)>,
+    ) {
+        if let Some((farm, position)) = farming_update {
+            <Farms<T>>::insert(first_asset, second_asset, farm);
+            <FarmingPositions<T>>::insert((first_asset, second_asset), who, position);
+        }
+    }
 
-            // Update exchange pools
-            exchange.update_pools(asset_swap_delta.first_asset_pool, asset_swap_delta.second_asset_pool)?;
+    // Initialize new exchange pool, updating exchanges cache
+    #[allow(clippy::too_many_arguments)]
+    fn do_initialize_exchange(
+        exchanges_cache: &mut ExchangesCache<T>,
+        sender: &T::AccountId,
+        first_asset: Asset<T::AssetId>,
+        first_asset_amount: BalanceOf<T>,
+        second_asset: Asset<T::AssetId>,
+        second_asset_amount: BalanceOf<T>,
+        curve: PoolCurve,
+        weight_schedule: Option<WeightSchedule<T::BlockNumber>>,
+    ) -> dispatch::DispatchResult {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+        // Ensure min asset amounts constraint satisfied
+        Self::ensure_min_asset_amounts(
+            first_asset,
+            first_asset_amount,
+            second_asset,
+            second_asset_amount,
+        )?;
+
+        // Curve parameters (e.g. weights) are given in the same order as assets, so adjust them too
+        let (curve, weight_schedule) = if Self::adjust_assets_order(first_asset, second_asset).2 {
+            (
+                curve.reversed(),
+                weight_schedule.map(|weight_schedule| weight_schedule.reversed()),
+            )
+        } else {
+            (curve, weight_schedule)
+        };
+
+        // Adjust assets and their respective amount order
+        let (first_asset, first_asset_amount, second_asset, second_asset_amount) =
+            Self::adjust_assets_amount_order(
+                first_asset,
+                first_asset_amount,
+                second_asset,
+                second_asset_amount,
+            );
+
+        let current_exchange = Self::cached_exchange(exchanges_cache, first_asset, second_asset);
+
+        // Ensure given exchange pool does not exist yet
+        current_exchange.ensure_not_exists()?;
+
+        // Ensure new liquidity pool can be launched successfully
+        current_exchange.ensure_launch()?;
+
+        // Ensure account has sufficient balance to initialize exchange
+        Self::ensure_sufficient_balances(
+            sender,
+            first_asset,
+            first_asset_amount,
+            second_asset,
+            second_asset_amount,
+        )?;
+
+        // Initialize new exchange pair
+        let (mut exchange, initial_shares) = Exchange::<T>::initialize_new(
+            first_asset_amount,
+            second_asset_amount,
+            sender.clone(),
+            curve,
+        )?;
+
+        if let Some(weight_schedule) = weight_schedule {
+            exchange.start_weight_schedule(weight_schedule)?;
+        }
 
-            //
-            // == MUTATION SAFE ==
-            //
+        //
+        // == MUTATION SAFE ==
+        //
+
+        // Slash respective asset amounts from given account to complete initialize exchange operation
+        Self::slash_assets(
+            sender,
+            first_asset,
+            first_asset_amount,
+            second_asset,
+            second_asset_amount,
+        );
 
-            // Perform exchange
+        Self::deposit_pool_updated_event(first_asset, second_asset, &exchange);
 
-            // Slash respective asset amount from given account to complete swap operation
-            Self::slash_asset(&sender, asset_in, asset_in_amount);
+        exchanges_cache.insert((first_asset, second_asset), exchange);
 
-            // Mint respective asset amount to given account to complete swap operation
-            Self::mint_asset(&sender, asset_out, asset_swap_delta.amount);
+        Self::index_pool(first_asset, second_asset);
 
-            // Charge treasury fee
-            let treasury_fee = if let Some((treasury_fee, dex_account_id)) = treasury_fee_data {
-                Self::mint_asset(&dex_account_id, asset_in, treasury_fee);
-                Some(treasury_fee)
-            } else {
-                None
-            };
+        Self::index_account_pool(sender, first_asset, second_asset, initial_shares);
 
-            // Update runtime exchange storage state
-            <Exchanges<T>>::insert(adjusted_first_asset_id, adjusted_second_asset_id, exchange);
+        Self::deposit_event(RawEvent::Initialized(
+            sender.clone(),
+            first_asset,
+            second_asset,
+            initial_shares,
+        ));
+        Ok(())
+    }
 
-            Self::deposit_event(RawEvent::Exchanged(
-                sender,
-                asset_in,
-                asset_in_amount,
-                asset_out,
-                asset_swap_delta.amount,
-                treasury_fee
-            ));
-            Ok(())
-        }
+    // Perform swap of some asset exact amount to another asset amount, crediting receiver account and updating exchanges cache
+    #[allow(clippy::too_many_arguments)]
+    fn do_swap(
+        exchanges_cache: &mut ExchangesCache<T>,
+        sender: &T::AccountId,
+        asset_in: Asset<T::AssetId>,
+        asset_in_amount: BalanceOf<T>,
+        asset_out: Asset<T::AssetId>,
+        min_asset_out_amount: BalanceOf<T>,
+        receiver: &T::AccountId,
+        referrer: Option<T::AccountId>,
+    ) -> Result<BalanceOf<T>, dispatch::DispatchError> {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(asset_in, asset_out)?;
+
+        // Ensure sender does not refer itself
+        ensure!(
+            referrer.as_ref() != Some(sender),
+            Error::<T>::InvalidReferrer
+        );
 
-        /// Used to invest liquidity into exchange pool
-        #[weight = 10_000]
-        pub fn invest_liquidity(origin, first_asset: Asset<T::AssetId>, second_asset: Asset<T::AssetId>, shares: BalanceOf<T>) -> dispatch::DispatchResult {
-            let sender = ensure_signed(origin)?;
+        let (adjusted_first_asset_id, adjusted_second_asset_id, adjsuted) =
+            Self::adjust_assets_order(asset_in, asset_out);
 
-            // Ensure assets are different
-            Self::ensure_valid_exchange(first_asset, second_asset)?;
+        let mut exchange = Self::cached_exchange(
+            exchanges_cache,
+            adjusted_first_asset_id,
+            adjusted_second_asset_id,
+        );
 
-            let (first_asset, second_asset, _) =
-                Self::adjust_assets_order(first_asset, second_asset);
+        // Ensure given exchange already exists
+        exchange.ensure_exists()?;
 
-            // Ensure given exchange already exists
-            let mut exchange = Self::ensure_exchange_exists(first_asset, second_asset)?;
+        // Ensure account has sufficient balance to perform swap
+        Self::ensure_sufficient_balance(sender, asset_in, asset_in_amount)?;
 
-            // Calculate costs for both first and second currencies, needed to get a given amount of shares
-            let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares)?;
+        // Calculate swap delata, treasury fee (if enabled) and referral fee (if referrer provided)
+        let (asset_swap_delta, treasury_fee_data, referral_fee_data) = if !adjsuted {
+            // Calculate first to second asset swap delta, treasury fee (if enabled) and referral fee (if referrer provided)
+            let (first_to_second_asset_swap_delta, treasury_fee_data, referral_fee_data) = exchange
+                .calculate_first_to_second_asset_swap(asset_in_amount, Some(sender), referrer)?;
+
+            // Ensure second asset amount is available for withdraw
+            exchange.ensure_second_asset_amount(
+                first_to_second_asset_swap_delta.amount,
+                min_asset_out_amount,
+            )?;
+
+            // Avoid overflow risks after exchange operation performed
+            Self::ensure_can_hold_balance(
+                receiver,
+                asset_out,
+                first_to_second_asset_swap_delta.amount,
+            )?;
+
+            (
+                first_to_second_asset_swap_delta,
+                treasury_fee_data,
+                referral_fee_data,
+            )
+        } else {
+            // Calculate second to first asset swap delta, treasury fee (if enabled) and referral fee (if referrer provided)
+            let (second_to_first_asset_swap_delta, treasury_fee_data, referral_fee_data) = exchange
+                .calculate_second_to_first_asset_swap(asset_in_amount, Some(sender), referrer)?;
+
+            // Ensure first asset amount is available for withdraw
+            exchange.ensure_first_asset_amount(
+                second_to_first_asset_swap_delta.amount,
+                min_asset_out_amount,
+            )?;
+
+            // Avoid overflow risks after exchange operation performed
+            Self::ensure_can_hold_balance(
+                receiver,
+                asset_out,
+                second_to_first_asset_swap_delta.amount,
+            )?;
+
+            (
+                second_to_first_asset_swap_delta,
+                treasury_fee_data,
+                referral_fee_data,
+            )
+        };
+
+        // Ensure swap does not move pools ratio beyond the limit
+        Self::ensure_price_impact(
+            &exchange,
+            adjusted_first_asset_id,
+            adjusted_second_asset_id,
+            &asset_swap_delta,
+            !adjsuted,
+        )?;
+
+        // Ensure swap does not move price too far from its recent average
+        Self::ensure_oracle_deviation(&exchange, &asset_swap_delta)?;
+
+        // Joint fee, applied to the swap (calculated before pools update may change exchange volatility)
+        let joint_fee = exchange.calculate_joint_fee(asset_in_amount, Some(sender))?;
+
+        // Update exchange pools
+        exchange.update_pools(
+            asset_swap_delta.first_asset_pool,
+            asset_swap_delta.second_asset_pool,
+        )?;
+
+        //
+        // == MUTATION SAFE ==
+        //
+
+        // Perform exchange
+
+        // Slash respective asset amount from given account to complete swap operation
+        Self::slash_asset(sender, asset_in, asset_in_amount);
+
+        // Mint respective asset amount to receiver account to complete swap operation
+        Self::mint_asset(receiver, asset_out, asset_swap_delta.amount);
+
+        // Charge treasury fee
+        let treasury_fee = if let Some((treasury_fee, dex_account_id)) = treasury_fee_data {
+            Self::mint_asset(&dex_account_id, asset_in, treasury_fee);
+            Some(treasury_fee)
+        } else {
+            None
+        };
+
+        // Pay referral fee
+        let referral_fee = if let Some((referral_fee, referrer)) = referral_fee_data {
+            Self::mint_asset(&referrer, asset_in, referral_fee);
+            Some((referrer, referral_fee))
+        } else {
+            None
+        };
+
+        Self::deposit_pool_updated_event(
+            adjusted_first_asset_id,
+            adjusted_second_asset_id,
+            &exchange,
+        );
 
-            // Ensure account has sufficient balances to perform invest operation
-            Self::ensure_sufficient_balances(&sender, first_asset, first_asset_cost, second_asset, second_asset_cost)?;
+        exchanges_cache.insert(
+            (adjusted_first_asset_id, adjusted_second_asset_id),
+            exchange,
+        );
 
-            // Invest funds into exchange
-            exchange.invest(first_asset_cost, second_asset_cost, shares, &sender)?;
+        Self::deposit_event(RawEvent::Exchanged(
+            sender.clone(),
+            receiver.clone(),
+            asset_in,
+            asset_in_amount,
+            asset_out,
+            asset_swap_delta.amount,
+            joint_fee,
+            treasury_fee,
+            referral_fee,
+        ));
+        Ok(asset_swap_delta.amount)
+    }
 
-            //
-            // == MUTATION SAFE ==
-            //
+    // Initialize exchange pool at genesis, crediting initial shares to the owner (no assets are slashed)
+    fn initialize_genesis_exchange(
+        first_asset: Asset<T::AssetId>,
+        first_asset_amount: BalanceOf<T>,
+        second_asset: Asset<T::AssetId>,
+        second_asset_amount: BalanceOf<T>,
+        owner: &T::AccountId,
+    ) -> dispatch::DispatchResult {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+        // Ensure min asset amounts constraint satisfied
+        Self::ensure_min_asset_amounts(
+            first_asset,
+            first_asset_amount,
+            second_asset,
+            second_asset_amount,
+        )?;
+
+        // Adjust assets and their respective amount order
+        let (first_asset, first_asset_amount, second_asset, second_asset_amount) =
+            Self::adjust_assets_amount_order(
+                first_asset,
+                first_asset_amount,
+                second_asset,
+                second_asset_amount,
+            );
 
-            // Slash user assets
-            Self::slash_assets(&sender, first_asset, first_asset_cost, second_asset, second_asset_cost);
+        // Ensure given exchange pool does not exist yet
+        Self::ensure_exchange_not_exists(first_asset, second_asset)?;
 
-            // Update runtime exchange storage state
-            <Exchanges<T>>::insert(first_asset, second_asset, exchange);
+        let (exchange, initial_shares) = Exchange::<T>::initialize_new(
+            first_asset_amount,
+            second_asset_amount,
+            owner.clone(),
+            PoolCurve::ConstantProduct,
+        )?;
 
-            Self::deposit_event(RawEvent::Invested(sender, first_asset, second_asset, shares));
-            Ok(())
-        }
+        <Exchanges<T>>::insert(first_asset, second_asset, exchange);
 
-        /// Used to divest liquidity from exchange pool
-        #[weight = 10_000]
-        pub fn divest_liquidity(
-            origin,
-            first_asset: Asset<T::AssetId>,
-            second_asset: Asset<T::AssetId>,
-            shares_burned:  BalanceOf<T>,
-            min_first_asset_received: BalanceOf<T>,
-            min_second_asset_received: BalanceOf<T>
-        ) -> dispatch::DispatchResult {
-            let sender = ensure_signed(origin)?;
+        Self::index_pool(first_asset, second_asset);
 
-            // Ensure assets are different
-            Self::ensure_valid_exchange(first_asset, second_asset)?;
+        Self::index_account_pool(owner, first_asset, second_asset, initial_shares);
+        Ok(())
+    }
 
-            let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+    // Invest liquidity into exchange pool, updating exchanges cache
+    fn do_invest_liquidity(
+        exchanges_cache: &mut ExchangesCache<T>,
+        sender: &T::AccountId,
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        shares: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+        let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+
+        let mut exchange = Self::cached_exchange(exchanges_cache, first_asset, second_asset);
+
+        // Ensure given exchange already exists
+        exchange.ensure_exists()?;
+
+        // Calculate costs for both first and second currencies, needed to get a given amount of shares
+        let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares)?;
+
+        // Ensure account has sufficient balances to perform invest operation
+        Self::ensure_sufficient_balances(
+            sender,
+            first_asset,
+            first_asset_cost,
+            second_asset,
+            second_asset_cost,
+        )?;
+
+        // Settle liquidity mining rewards, accumulated before shares changed
+        let shares_before = exchange.shares(sender);
+        let farming_update = Self::calculate_farming_update(
+            first_asset,
+            second_asset,
+            sender,
+            exchange.total_shares,
+            shares_before,
+            shares_before
+                .checked_add(&shares)
+                .ok_or(Error::<T>::OverflowOccured)?,
+        )?;
+
+        // Invest funds into exchange
+        exchange.invest(first_asset_cost, second_asset_cost, shares, sender)?;
+
+        //
+        // == MUTATION SAFE ==
+        //
+
+        // Slash user assets
+        Self::slash_assets(
+            sender,
+            first_asset,
+            first_asset_cost,
+            second_asset,
+            second_asset_cost,
+        );
 
-            // Ensure given exchange already exists
-            let mut exchange = Self::ensure_exchange_exists(first_asset, second_asset)?;
+        Self::update_farming(first_asset, second_asset, sender, farming_update);
 
-            // Perform all necessary checks to ensure that given amount of shares can be burned succesfully
-            exchange.ensure_burned_shares(&sender, shares_burned)?;
+        Self::index_account_pool(sender, first_asset, second_asset, exchange.shares(sender));
 
-            let (first_asset_cost, second_asset_cost) = exchange.calculate_costs(shares_burned)?;
+        Self::deposit_pool_updated_event(first_asset, second_asset, &exchange);
 
-            // Ensure divest expectations satisfied
-            Self::ensure_divest_expectations(first_asset_cost, second_asset_cost, min_first_asset_received, min_second_asset_received)?;
+        exchanges_cache.insert((first_asset, second_asset), exchange);
 
-            // Avoid overflow risks
-            Self::ensure_can_hold_balances(&sender, first_asset, first_asset_cost, second_asset, second_asset_cost)?;
+        Self::deposit_event(RawEvent::Invested(
+            sender.clone(),
+            first_asset,
+            second_asset,
+            shares,
+            first_asset_cost,
+            second_asset_cost,
+        ));
+        Ok(())
+    }
 
-            // Divest funds from exchange
-            exchange.divest(first_asset_cost, second_asset_cost, shares_burned, &sender)?;
+    // Divest liquidity from exchange pool, updating exchanges cache
+    fn do_divest_liquidity(
+        exchanges_cache: &mut ExchangesCache<T>,
+        sender: &T::AccountId,
+        first_asset: Asset<T::AssetId>,
+        second_asset: Asset<T::AssetId>,
+        shares_burned: BalanceOf<T>,
+        min_first_asset_received: BalanceOf<T>,
+        min_second_asset_received: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        // Ensure assets are different
+        Self::ensure_valid_exchange(first_asset, second_asset)?;
+
+        let (first_asset, second_asset, _) = Self::adjust_assets_order(first_asset, second_asset);
+
+        let mut exchange = Self::cached_exchange(exchanges_cache, first_asset, second_asset);
+
+        // Ensure given exchange already exists
+        exchange.ensure_exists()?;
+
+        // Perform all necessary checks to ensure that given amount of shares can be burned succesfully
+        exchange.ensure_burned_shares(sender, shares_burned)?;
+
+        let (first_asset_cost, second_asset_cost) =
+            exchange.calculate_divest_amounts(shares_burned)?;
+
+        // Ensure divest expectations satisfied
+        Self::ensure_divest_expectations(
+            first_asset_cost,
+            second_asset_cost,
+            min_first_asset_received,
+            min_second_asset_received,
+        )?;
+
+        // Avoid overflow risks
+        Self::ensure_can_hold_balances(
+            sender,
+            first_asset,
+            first_asset_cost,
+            second_asset,
+            second_asset_cost,
+        )?;
+
+        // Settle liquidity mining rewards, accumulated before shares changed
+        let shares_before = exchange.shares(sender);
+        let farming_update = Self::calculate_farming_update(
+            first_asset,
+            second_asset,
+            sender,
+            exchange.total_shares,
+            shares_before,
+            shares_before
+                .checked_sub(&shares_burned)
+                .ok_or(Error::<T>::UnderflowOccured)?,
+        )?;
+
+        // Divest funds from exchange
+        exchange.divest(first_asset_cost, second_asset_cost, shares_burned, sender)?;
+
+        //
+        // == MUTATION SAFE ==
+        //
+
+        Self::mint_assets(
+            sender,
+            first_asset,
+            first_asset_cost,
+            second_asset,
+            second_asset_cost,
+        );
 
-            //
-            // == MUTATION SAFE ==
-            //
+        Self::update_farming(first_asset, second_asset, sender, farming_update);
 
-            Self::mint_assets(&sender, first_asset, first_asset_cost, second_asset, second_asset_cost);
+        Self::index_account_pool(sender, first_asset, second_asset, exchange.shares(sender));
 
-            // Update runtime exchange storage state
-            <Exchanges<T>>::insert(first_asset, second_asset, exchange);
+        Self::deposit_pool_updated_event(first_asset, second_asset, &exchange);
 
-            Self::deposit_event(RawEvent::Divested(sender, first_asset, second_asset, shares_burned));
-            Ok(())
-        }
-    }
-}
+        exchanges_cache.insert((first_asset, second_asset), exchange);
 
-impl<T: Trait> Module<T> {
-    /// Ensure exchange assets are different
-    pub fn ensure_valid_exchange(
-        asset_in: Asset<T::AssetId>,
-        asset_out: Asset<T::AssetId>,
-    ) -> Result<(), Error<T>> {
-        match (asset_in, asset_out) {
-            (Asset::MainNetworkCurrency, Asset::MainNetworkCurrency) => {
-                Err(Error::<T>::InvalidExchange)
-            }
-            (Asset::ParachainAsset(asset_in_id), Asset::ParachainAsset(asset_out_id))
-                if asset_in_id == asset_out_id =>
-            {
-                Err(Error::<T>::InvalidExchange)
-            }
-            _ => Ok(()),
-        }
+        Self::deposit_event(RawEvent::Divested(
+            sender.clone(),
+            first_asset,
+            second_asset,
+            shares_burned,
+            first_asset_cost,
+            second_asset_cost,
+        ));
+        Ok(())
     }
 
     /// Slash respective assets amount from given account after invest or exchange operation performed
@@ -509,11 +2655,7 @@ impl<T: Trait> Module<T> {
         second_asset: Asset<T::AssetId>,
     ) -> Result<Exchange<T>, Error<T>> {
         let exchange = Self::exchanges(first_asset, second_asset);
-
-        ensure!(
-            exchange.invariant > BalanceOf::<T>::zero(),
-            Error::<T>::ExchangeNotExists
-        );
+        exchange.ensure_exists()?;
         Ok(exchange)
     }
 
@@ -587,12 +2729,7 @@ impl<T: Trait> Module<T> {
         first_asset: Asset<T::AssetId>,
         second_asset: Asset<T::AssetId>,
     ) -> dispatch::DispatchResult {
-        let first_exchange = Self::exchanges(first_asset, second_asset);
-
-        ensure!(
-            first_exchange.invariant == BalanceOf::<T>::zero(),
-            Error::<T>::ExchangeAlreadyExists
-        );
+        Self::exchanges(first_asset, second_asset).ensure_not_exists()?;
         Ok(())
     }
 
@@ -657,6 +2794,91 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    /// Ensure account has sufficient free balance to reserve given asset amount
+    pub fn ensure_can_reserve(
+        who: &T::AccountId,
+        asset: Asset<T::AssetId>,
+        amount: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        match asset {
+            Asset::MainNetworkCurrency => {
+                let new_balance = T::Currency::free_balance(who)
+                    .checked_sub(&amount)
+                    .ok_or(Error::<T>::InsufficientMainNetworkAssetAmount)?;
+
+                T::Currency::ensure_can_withdraw(
+                    who,
+                    amount,
+                    WithdrawReason::Reserve.into(),
+                    new_balance,
+                )?;
+                Ok(())
+            }
+            Asset::ParachainAsset(asset_id) if Self::asset_balances(who, asset_id) >= amount => {
+                Self::reserved_asset_balances(who, asset_id)
+                    .checked_add(&amount)
+                    .ok_or(Error::<T>::OverflowOccured)?;
+                Ok(())
+            }
+            _ => Err(Error::<T>::InsufficientParachainAssetAmount.into()),
+        }
+    }
+
+    /// Move given asset amount from free to reserved balance of a given account
+    pub fn reserve_asset(
+        who: &T::AccountId,
+        asset: Asset<T::AssetId>,
+        amount: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        match asset {
+            Asset::MainNetworkCurrency => T::Currency::reserve(who, amount),
+            Asset::ParachainAsset(asset_id) => {
+                let new_balance = Self::asset_balances(who, asset_id)
+                    .checked_sub(&amount)
+                    .ok_or(Error::<T>::InsufficientParachainAssetAmount)?;
+
+                let new_reserved_balance = Self::reserved_asset_balances(who, asset_id)
+                    .checked_add(&amount)
+                    .ok_or(Error::<T>::OverflowOccured)?;
+
+                <AssetBalances<T>>::insert(who, asset_id, new_balance);
+                <ReservedAssetBalances<T>>::insert(who, asset_id, new_reserved_balance);
+                Ok(())
+            }
+        }
+    }
+
+    /// Move given asset amount (or whole reserved balance, if less) from reserved to free balance of a given account.
+    /// Returns amount, which could not be unreserved.
+    pub fn unreserve_asset(
+        who: &T::AccountId,
+        asset: Asset<T::AssetId>,
+        amount: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        match asset {
+            Asset::MainNetworkCurrency => T::Currency::unreserve(who, amount),
+            Asset::ParachainAsset(asset_id) => {
+                let reserved_balance = Self::reserved_asset_balances(who, asset_id);
+
+                let unreserved_amount = amount.min(reserved_balance);
+
+                if reserved_balance == unreserved_amount {
+                    <ReservedAssetBalances<T>>::remove(who, asset_id);
+                } else {
+                    <ReservedAssetBalances<T>>::insert(
+                        who,
+                        asset_id,
+                        reserved_balance - unreserved_amount,
+                    );
+                }
+
+                Self::mint_asset(who, asset, unreserved_amount);
+
+                amount - unreserved_amount
+            }
+        }
+    }
+
     /// Avoid overflow risks after exchange or divest operation performed
     pub fn ensure_can_hold_balances(
         who: &T::AccountId,
@@ -698,6 +2920,14 @@ impl<T: Trait> Module<T> {
         Self::ensure_min_asset_amount(second_asset, second_asset_amount)
     }
 
+    /// Min amount of a given asset to perform operations with
+    pub fn min_asset_amount(asset: Asset<T::AssetId>) -> BalanceOf<T> {
+        match asset {
+            Asset::MainNetworkCurrency => T::MinMainNetworkAssetAmount::get(),
+            Asset::ParachainAsset(_) => T::MinParachainAssetAmount::get(),
+        }
+    }
+
     /// Ensure provided asset amount satisfy min amount restriction
     pub fn ensure_min_asset_amount(
         asset: Asset<T::AssetId>,
@@ -0,0 +1,43 @@
+use super::*;
+use crate::math::{fixed_one, mul_div_ceil, to_balance, to_u256};
+use sp_core::U256;
+
+/// Limit order identifier
+pub type LimitOrderId = u64;
+
+/// Max number of bisection steps, performed to find limit order partial fill amount
+pub const LIMIT_ORDER_FILL_SEARCH_STEPS: u32 = 32;
+
+/// Limit order: swap of asset in amount, filled against exchange pool (possibly partially) only at or above a given min price.
+/// Asset in amount is reserved up front on owner account together with order deposit.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct LimitOrder<T: Trait> {
+    // order owner, receives fills output and remaining funds refund
+    pub owner: T::AccountId,
+    // asset, swapped when order is filled
+    pub asset_in: Asset<T::AssetId>,
+    // asset, received when order is filled
+    pub asset_out: Asset<T::AssetId>,
+    // asset in amount, left to be filled
+    pub amount_left: BalanceOf<T>,
+    // min asset out amount per asset in amount (fixed point)
+    pub min_price: U256,
+    // block, order expires at
+    pub expiry: T::BlockNumber,
+    // block, order is checked against exchange pool at
+    pub next_check: T::BlockNumber,
+    // order deposit
+    pub deposit: BalanceOf<T>,
+}
+
+impl<T: Trait> LimitOrder<T> {
+    /// Min asset out amount, expected from filling given asset in amount at min price (rounded up)
+    pub fn min_out_amount(&self, asset_in_amount: BalanceOf<T>) -> Result<BalanceOf<T>, Error<T>> {
+        to_balance::<T>(mul_div_ceil::<T>(
+            to_u256::<T>(asset_in_amount),
+            self.min_price,
+            fixed_one(),
+        )?)
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+use sp_core::U256;
+use sp_runtime::traits::UniqueSaturatedInto;
+use sp_std::convert::TryFrom;
+
+/// Convert balance into U256, used to perform intermediate calculations without overflow risks
+pub fn to_u256<T: Trait>(amount: BalanceOf<T>) -> U256 {
+    let amount: u128 = amount.unique_saturated_into();
+    U256::from(amount)
+}
+
+/// Convert U256 intermediate calculation result back into balance
+pub fn to_balance<T: Trait>(amount: U256) -> Result<BalanceOf<T>, Error<T>> {
+    ensure!(
+        amount <= U256::from(u128::max_value()),
+        Error::<T>::OverflowOccured
+    );
+    BalanceOf::<T>::try_from(amount.low_u128()).map_err(|_| Error::<T>::OverflowOccured)
+}
+
+/// Calculate `a * b / c`, using U256 for intermediate result
+pub fn mul_div<T: Trait>(a: U256, b: U256, c: U256) -> Result<U256, Error<T>> {
+    a.checked_mul(b)
+        .map(|result| result.checked_div(c))
+        .flatten()
+        .ok_or(Error::<T>::UnderflowOrOverflowOccured)
+}
+
+/// Calculate `a / b`, rounding up
+pub fn div_ceil<T: Trait>(a: U256, b: U256) -> Result<U256, Error<T>> {
+    let result = a
+        .checked_div(b)
+        .ok_or(Error::<T>::UnderflowOrOverflowOccured)?;
+    if (a % b).is_zero() {
+        Ok(result)
+    } else {
+        result
+            .checked_add(U256::one())
+            .ok_or(Error::<T>::OverflowOccured)
+    }
+}
+
+/// Calculate `a * b / c`, rounding up
+pub fn mul_div_ceil<T: Trait>(a: U256, b: U256, c: U256) -> Result<U256, Error<T>> {
+    div_ceil::<T>(
+        a.checked_mul(b)
+            .ok_or(Error::<T>::UnderflowOrOverflowOccured)?,
+        c,
+    )
+}
+
+/// Calculate integer square root (rounded down), using Newton`s method
+pub fn integer_sqrt(value: U256) -> U256 {
+    if value.is_zero() {
+        return U256::zero();
+    }
+    let two = U256::from(2);
+    let mut result = value;
+    // ceil(value / 2), written this way to avoid overflow
+    let mut next = value / two + value % two;
+    while next < result {
+        result = next;
+        next = (result + value / result) / two;
+    }
+    result
+}
+
+/// Fixed point one, used in logarithm and exponent calculations
+pub fn fixed_one() -> U256 {
+    U256::from(1_000_000_000_000_000_000u128)
+}
+
+// ln(2) in fixed point representation
+fn fixed_ln_2() -> U256 {
+    U256::from(693_147_180_559_945_309u128)
+}
+
+/// Calculate `-ln(base)` for fixed point base in range (0, 1].
+/// Returns `None` for zero base, as its logarithm is not defined.
+pub fn fixed_neg_ln(base: U256) -> Option<U256> {
+    let one = fixed_one();
+    let two = U256::from(2);
+
+    // Normalize base into [1, 2) range: base = mantissa / 2^power
+    let mut mantissa = base;
+    let mut power = U256::zero();
+    while !mantissa.is_zero() && mantissa < one {
+        mantissa = mantissa * two;
+        power = power + U256::one();
+    }
+
+    // ln(mantissa) = 2 * atanh(z), where z = (mantissa - 1) / (mantissa + 1) < 1 / 3
+    let z = mantissa.checked_sub(one)? * one / (mantissa + one);
+    let z_squared = z * z / one;
+    let mut term = z;
+    let mut sum = U256::zero();
+    let mut n = U256::one();
+    while !term.is_zero() {
+        sum = sum + term / n;
+        term = term * z_squared / one;
+        n = n + two;
+    }
+
+    Some((power * fixed_ln_2()).saturating_sub(sum * two))
+}
+
+/// Calculate `e^(-exponent)` for non negative fixed point exponent
+pub fn fixed_exp_neg(exponent: U256) -> U256 {
+    let one = fixed_one();
+
+    // e^(-exponent) = 1 / (2^quotient * e^remainder), where remainder < ln(2)
+    let quotient = exponent / fixed_ln_2();
+    if quotient >= U256::from(128) {
+        return U256::zero();
+    }
+    let remainder = exponent - quotient * fixed_ln_2();
+
+    let mut term = one;
+    let mut sum = one;
+    let mut n = U256::one();
+    loop {
+        term = term * remainder / (one * n);
+        if term.is_zero() {
+            break;
+        }
+        sum = sum + term;
+        n = n + U256::one();
+    }
+
+    (one * one / sum) >> quotient.as_usize()
+}
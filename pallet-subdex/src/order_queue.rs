@@ -0,0 +1,25 @@
+use super::*;
+
+/// Order, waiting in the scheduled orders queue to be processed in `on_initialize`.
+/// Scheduled order funds are reserved on its owner account together with order deposit till order is closed.
+/// Closing order leaves its queue entry in place, the entry is skipped, once order is not found.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScheduledOrder {
+    /// Dollar cost averaging order, due to perform its next period swap
+    Dca(DcaOrderId),
+    /// Limit order, due to be checked against exchange pool
+    Limit(LimitOrderId),
+    /// Stop-loss order, due to be checked for trigger
+    StopLoss(StopLossOrderId),
+}
+
+/// Swap computation weight (weighted pool curve is the most expensive one), excluding storage access
+pub const SWAP_COMPUTATION_WEIGHT: Weight = 25_000_000;
+
+/// Storage reads, performed by a single swap through the regular swap path
+/// (exchange, pool limits, fee parameters, treasury and both accounts balances)
+pub const SWAP_READS: Weight = 12;
+
+/// Storage writes, performed by a single swap through the regular swap path (exchange and balances)
+pub const SWAP_WRITES: Weight = 5;
@@ -0,0 +1,31 @@
+use super::*;
+use sp_core::U256;
+
+/// Stop-loss order identifier
+pub type StopLossOrderId = u64;
+
+/// Stop-loss order: swap of asset in amount, triggered once time weighted average price of asset in
+/// (in units of asset out) falls to or below a given trigger price. Asset in amount is reserved up front on owner account
+/// together with order deposit.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct StopLossOrder<T: Trait> {
+    // order owner, receives swap output or reserved funds refund
+    pub owner: T::AccountId,
+    // asset, swapped when order is triggered
+    pub asset_in: Asset<T::AssetId>,
+    // asset, received when order is triggered
+    pub asset_out: Asset<T::AssetId>,
+    // asset in amount, swapped when order is triggered
+    pub amount: BalanceOf<T>,
+    // time weighted average price of asset in, order is triggered at (fixed point)
+    pub trigger_price: U256,
+    // min asset out amount, expected from the swap
+    pub min_out: BalanceOf<T>,
+    // block, order expires at
+    pub expiry: T::BlockNumber,
+    // block, order trigger is checked at
+    pub next_check: T::BlockNumber,
+    // order deposit
+    pub deposit: BalanceOf<T>,
+}